@@ -1,10 +1,68 @@
+mod serve;
+
 #[derive(clap::Parser, core::fmt::Debug)]
 #[clap(author, version, about)]
+struct Cli {
+    /// Network profile to connect to
+    #[clap(long, value_enum, default_value = "mainnet", global = true)]
+    network: Network,
+    /// RPC endpoint replacing the ones of the network profile, can be repeated for failover
+    #[clap(long, value_parser, global = true)]
+    rpc_url: Vec<String>,
+    /// Genesis hash (starts with "0x") the node must have, replacing the one of the network profile
+    #[clap(long, value_parser, global = true)]
+    genesis_hash: Option<String>,
+    /// Runtime spec version the node must run, with --transaction-version
+    #[clap(long, value_parser, requires = "transaction_version", global = true)]
+    spec_version: Option<u32>,
+    /// Runtime transaction version the node must run, with --spec-version
+    #[clap(long, value_parser, requires = "spec_version", global = true)]
+    transaction_version: Option<u32>,
+    /// Keep going through live metadata when the runtime does not match the built-in one
+    #[clap(long, action, default_value_t = false, global = true)]
+    allow_dynamic: bool,
+    /// JSON file of the spending policies checked before signing
+    #[clap(long, value_parser, global = true)]
+    policy_file: Option<std::path::PathBuf>,
+    /// JSON file keeping the amounts spent today, so that daily limits hold across runs
+    #[clap(long, value_parser, global = true)]
+    spending_file: Option<std::path::PathBuf>,
+    /// JSON file journaling every transaction before its broadcast, unsettled ones are reconciled on startup
+    #[clap(long, value_parser, global = true)]
+    journal_file: Option<std::path::PathBuf>,
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, core::fmt::Debug)]
+enum Network {
+    Mainnet,
+    Testnet,
+    Local,
+}
+
+impl Network {
+    fn to_config(self) -> nagara_simple_wallet::NetworkConfig {
+        match self {
+            Self::Mainnet => nagara_simple_wallet::NetworkConfig::mainnet(),
+            Self::Testnet => nagara_simple_wallet::NetworkConfig::testnet(),
+            Self::Local => nagara_simple_wallet::NetworkConfig::local(),
+        }
+    }
+}
+
+#[derive(clap::Subcommand, core::fmt::Debug)]
 enum Commands {
     Check {
         /// SS58 address to check
         #[clap(short, long)]
         account: String,
+        /// Block hash (starts with "0x") or number to read the balance at, defaults to the latest block
+        #[clap(long, value_parser, conflicts_with = "at_time")]
+        at_block: Option<String>,
+        /// RFC 3339 instant (e.g. "2023-11-30T23:59:59Z") to read the balance at
+        #[clap(long, value_parser = humantime::parse_rfc3339_weak)]
+        at_time: Option<std::time::SystemTime>,
     },
     Transfer {
         /// Sender private key hex (starts with "0x"), can also be mnemonic. Always surround it with ""
@@ -19,39 +77,480 @@ enum Commands {
         /// Sender use ed25519 instead of sr25519
         #[clap(short, long, action, default_value_t = false)]
         edward: bool,
+        /// Key sending the transfer only once, the journaled transaction is reported when it was already sent. Needs --journal-file
+        #[clap(long, value_parser)]
+        idempotency_key: Option<String>,
+        #[clap(flatten)]
+        tx_args: TxArgs,
+    },
+    Tx {
+        /// Sender private key hex (starts with "0x"), can also be mnemonic. Always surround it with ""
+        #[clap(short, long, value_parser)]
+        private_key: String,
+        /// Sender use ed25519 instead of sr25519
+        #[clap(short, long, action, default_value_t = false)]
+        edward: bool,
+        /// Pallet of the call as named in the runtime metadata (e.g. "System")
+        #[clap(long, value_parser)]
+        pallet: String,
+        /// Call as named in the runtime metadata (e.g. "remark")
+        #[clap(long, value_parser)]
+        call: String,
+        /// JSON array of the call arguments in order, or object keyed by argument name
+        #[clap(long, value_parser, default_value = "[]")]
+        args: String,
+        #[clap(flatten)]
+        tx_args: TxArgs,
+    },
+    Storage {
+        /// Pallet of the storage entry (e.g. "balances")
+        #[clap(long, value_parser)]
+        pallet: String,
+        /// Storage entry (e.g. "total_issuance")
+        #[clap(long, value_parser)]
+        entry: String,
+        /// JSON array of the map keys in order
+        #[clap(long, value_parser, default_value = "[]")]
+        keys: String,
+        /// Block hash (starts with "0x") or number to read the state at, defaults to the latest block
+        #[clap(long, value_parser)]
+        at_block: Option<String>,
+    },
+    Constant {
+        /// Pallet of the constant (e.g. "balances")
+        #[clap(long, value_parser)]
+        pallet: String,
+        /// Constant (e.g. "existential_deposit")
+        #[clap(long, value_parser)]
+        name: String,
+    },
+    Watch {
+        /// SS58 address to watch
+        #[clap(short, long)]
+        account: String,
+        /// Report changes of best blocks, which may be reverted, instead of finalized ones
+        #[clap(long, action, default_value_t = false)]
+        best: bool,
+    },
+    Deposits {
+        /// SS58 address receiving deposits, can be repeated
+        #[clap(short, long)]
+        account: Vec<String>,
+        /// File listing SS58 addresses receiving deposits, one per line
+        #[clap(long, value_parser)]
+        accounts_file: Option<std::path::PathBuf>,
+        /// File keeping the last scanned block, the scan resumes right after it
+        #[clap(long, value_parser)]
+        cursor_file: Option<std::path::PathBuf>,
+        /// First block to scan when there is no cursor yet, defaults to the finalized head
+        #[clap(long, value_parser)]
+        start_block: Option<u32>,
+        /// Finalized blocks, the deposit block included, required before reporting a deposit
+        #[clap(long, value_parser, default_value_t = 1)]
+        min_confirmations: u32,
+        /// Database indexing the watched addresses while scanning, blocks it covers aren't read again
+        #[clap(long, value_parser)]
+        index_db: Option<std::path::PathBuf>,
+    },
+    History {
+        /// SS58 address to list the movements of
+        #[clap(short, long)]
+        account: String,
+        /// First block to scan, required without an index file
+        #[clap(long, value_parser)]
+        from_block: Option<u32>,
+        /// Last block to scan, defaults to the latest finalized block
+        #[clap(long, value_parser)]
+        to_block: Option<u32>,
+        /// File keeping the history between runs, only new blocks are scanned
+        #[clap(long, value_parser)]
+        index_file: Option<std::path::PathBuf>,
+    },
+    Report {
+        /// SS58 address to report on, can be repeated
+        #[clap(short, long)]
+        account: Vec<String>,
+        /// File listing SS58 addresses to report on, one per line
+        #[clap(long, value_parser)]
+        accounts_file: Option<std::path::PathBuf>,
+        /// File listing the private keys hex (starts with "0x") or mnemonics of the wallet accounts, all of them are reported on
+        #[clap(long, value_parser)]
+        keys_file: Option<std::path::PathBuf>,
+        /// Wallet accounts of the keys file use ed25519 instead of sr25519
+        #[clap(short, long, action, default_value_t = false)]
+        edward: bool,
+        /// RFC 3339 instant the report starts at, included
+        #[clap(long, value_parser = humantime::parse_rfc3339_weak)]
+        from: std::time::SystemTime,
+        /// RFC 3339 instant the report ends at, excluded, defaults to now
+        #[clap(long, value_parser = humantime::parse_rfc3339_weak)]
+        to: Option<std::time::SystemTime>,
+        /// Period the totals are grouped by
+        #[clap(long, value_enum, default_value = "month")]
+        period: Period,
+        #[clap(long, value_enum, default_value = "csv")]
+        format: ReportFormat,
+        /// File to write the report to, printed if omitted
+        #[clap(long, value_parser)]
+        output: Option<std::path::PathBuf>,
+        /// File to write the CSV totals to, printed if omitted
+        #[clap(long, value_parser)]
+        totals_output: Option<std::path::PathBuf>,
+    },
+    Block {
+        #[clap(subcommand)]
+        command: BlockCommands,
+    },
+    Extrinsic {
+        #[clap(subcommand)]
+        command: ExtrinsicCommands,
+    },
+    Status {
+        /// Keep following the chain and report when finality stalls or recovers
+        #[clap(long, action, default_value_t = false)]
+        monitor: bool,
+        /// Best blocks allowed to wait for finality before reporting a stall
+        #[clap(long, value_parser, default_value_t = 10)]
+        max_lag: u32,
+    },
+    Pending {
+        /// SS58 address of the sender
+        #[clap(short, long)]
+        account: String,
+    },
+    Replace {
+        /// Sender private key hex (starts with "0x"), can also be mnemonic. Always surround it with ""
+        #[clap(short, long, value_parser)]
+        private_key: String,
+        /// Sender use ed25519 instead of sr25519
+        #[clap(short, long, action, default_value_t = false)]
+        edward: bool,
+        /// Replace the pending transaction with an empty remark instead of signing it again
+        #[clap(short, long, action, default_value_t = false)]
+        cancel: bool,
+        #[clap(flatten)]
+        tx_args: TxArgs,
+    },
+    Serve {
+        /// Address and port the HTTP API listens on
+        #[clap(long, value_parser, default_value = "127.0.0.1:8080")]
+        listen: std::net::SocketAddr,
+        /// Bearer token the callers of the API must send
+        #[clap(
+            long,
+            value_parser,
+            env = "NAGARA_WALLET_API_TOKEN",
+            hide_env_values = true
+        )]
+        api_token: String,
+        /// Encrypted keystore of the paying accounts, see the keystore command
+        #[clap(long, value_parser, required_unless_present = "keys_file")]
+        keystore_file: Option<std::path::PathBuf>,
+        /// Passphrase unlocking the keystore
+        #[clap(
+            long,
+            value_parser,
+            env = "NAGARA_WALLET_KEYSTORE_PASSPHRASE",
+            hide_env_values = true,
+            requires = "keystore_file"
+        )]
+        passphrase: Option<String>,
+        /// File listing the private keys hex (starts with "0x") or mnemonics of the paying accounts, one per line, unencrypted
+        #[clap(long, value_parser)]
+        keys_file: Option<std::path::PathBuf>,
+        /// Paying accounts of the keys file use ed25519 instead of sr25519
+        #[clap(short, long, action, default_value_t = false)]
+        edward: bool,
+    },
+    Keystore {
+        /// Encrypted keystore file, created by the first added account
+        #[clap(long, value_parser)]
+        keystore_file: std::path::PathBuf,
+        #[clap(subcommand)]
+        command: KeystoreCommands,
+    },
+}
+
+#[derive(clap::Subcommand, core::fmt::Debug)]
+enum KeystoreCommands {
+    /// Encrypt an account into the keystore
+    Add {
+        /// Private key hex (starts with "0x"), can also be mnemonic. Always surround it with ""
+        #[clap(short, long, value_parser)]
+        private_key: String,
+        /// Account use ed25519 instead of sr25519
+        #[clap(short, long, action, default_value_t = false)]
+        edward: bool,
+        /// Passphrase encrypting the account, the same for every account of the keystore
+        #[clap(
+            long,
+            value_parser,
+            env = "NAGARA_WALLET_KEYSTORE_PASSPHRASE",
+            hide_env_values = true
+        )]
+        passphrase: String,
+    },
+    /// Print the addresses of the keystore
+    List,
+    /// Remove an account from the keystore
+    Remove {
+        /// SS58 address of the account
+        #[clap(short, long)]
+        account: String,
+    },
+}
+
+#[derive(clap::Subcommand, core::fmt::Debug)]
+enum BlockCommands {
+    /// Print the header, author, extrinsics and events of a block
+    Show {
+        /// Block hash (starts with "0x") or number, defaults to the latest block
+        #[clap(long, value_parser)]
+        at_block: Option<String>,
+    },
+}
+
+#[derive(clap::Subcommand, core::fmt::Debug)]
+enum ExtrinsicCommands {
+    /// Print the signer, call, arguments, outcome and events of an extrinsic
+    Show {
+        /// Block hash (starts with "0x") or number of the extrinsic
+        #[clap(long, value_parser)]
+        at_block: String,
+        /// Index of the extrinsic in the block
+        #[clap(long, value_parser)]
+        index: u32,
+    },
+    /// Decode a hex extrinsic against the metadata of the current runtime
+    Decode {
+        /// Extrinsic hex (starts with "0x"), with or without its length prefix
+        #[clap(long, value_parser)]
+        hex: String,
     },
 }
 
-fn get_decimal_scaler() -> bigdecimal::BigDecimal {
-    let decimal_place =
-        ss58_registry::Token::from(ss58_registry::TokenRegistry::Ngr).decimals as u32;
+#[derive(clap::ValueEnum, Clone, Copy, core::fmt::Debug)]
+enum Period {
+    Day,
+    Month,
+    Year,
+}
+
+impl Period {
+    fn to_report_period(self) -> nagara_simple_wallet::ReportPeriod {
+        match self {
+            Self::Day => nagara_simple_wallet::ReportPeriod::Day,
+            Self::Month => nagara_simple_wallet::ReportPeriod::Month,
+            Self::Year => nagara_simple_wallet::ReportPeriod::Year,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, core::fmt::Debug)]
+enum ReportFormat {
+    Csv,
+    Json,
+}
 
-    <bigdecimal::BigDecimal as bigdecimal::FromPrimitive>::from_i64(10i64.pow(decimal_place))
-        .unwrap()
+#[derive(clap::Args, core::fmt::Debug)]
+struct TxArgs {
+    /// NGR tip for the block author in decimal
+    #[clap(long, value_parser)]
+    tip: Option<bigdecimal::BigDecimal>,
+    /// Make the transaction mortal for this many blocks, immortal if omitted
+    #[clap(long, value_parser)]
+    era_period: Option<u64>,
+    /// Sign with this nonce instead of the next one known by the chain
+    #[clap(long, value_parser)]
+    nonce: Option<u64>,
+    /// Block hash (starts with "0x") the mortal era starts from, defaults to the latest block
+    #[clap(long, value_parser)]
+    at_block: Option<String>,
+    /// Execute the transaction with a dry run before broadcasting it
+    #[clap(long, action, default_value_t = false)]
+    dry_run: bool,
 }
 
-impl Commands {
+impl TxArgs {
+    fn to_options(
+        &self,
+        network: &nagara_simple_wallet::NetworkConfig,
+    ) -> anyhow::Result<nagara_simple_wallet::TxOptions> {
+        let mut options = nagara_simple_wallet::TxOptions::new().dry_run(self.dry_run);
+
+        if let Some(tip) = &self.tip {
+            options = options.tip(to_smallest_unit(tip.clone(), network)?);
+        }
+
+        if let Some(era_period) = self.era_period {
+            options = options.mortal(era_period);
+        }
+
+        if let Some(nonce) = self.nonce {
+            options = options.nonce(nonce);
+        }
+
+        if let Some(at_block) = &self.at_block {
+            options = options.at_block(parse_block_hash(at_block)?);
+        }
+
+        Ok(options)
+    }
+}
+
+fn get_decimal_scaler(
+    network: &nagara_simple_wallet::NetworkConfig,
+) -> anyhow::Result<bigdecimal::BigDecimal> {
+    let decimal_place = network.token_decimals as u32;
+    let scaler = 10u128.checked_pow(decimal_place).ok_or(anyhow::anyhow!(
+        "{} decimals don't fit in 128 bits!",
+        network.token_decimals
+    ))?;
+
+    Ok(<bigdecimal::BigDecimal as bigdecimal::FromPrimitive>::from_u128(scaler).unwrap())
+}
+
+fn to_smallest_unit(
+    amount: bigdecimal::BigDecimal,
+    network: &nagara_simple_wallet::NetworkConfig,
+) -> anyhow::Result<u128> {
+    let amount_decimal = std::ops::Mul::mul(amount, get_decimal_scaler(network)?);
+
+    bigdecimal::ToPrimitive::to_u128(&amount_decimal).ok_or(anyhow::anyhow!("Bad digits!"))
+}
+
+fn to_decimal(
+    amount: u128,
+    network: &nagara_simple_wallet::NetworkConfig,
+) -> anyhow::Result<bigdecimal::BigDecimal> {
+    let amount_decimal =
+        <bigdecimal::BigDecimal as bigdecimal::FromPrimitive>::from_u128(amount).unwrap();
+
+    Ok(std::ops::Div::div(
+        amount_decimal,
+        get_decimal_scaler(network)?,
+    ))
+}
+
+fn parse_block_hash(block_hash: &str) -> anyhow::Result<nagara_simple_wallet::BlockHash> {
+    let block_hash_bytes = hex::decode(block_hash.trim_start_matches("0x"))?;
+    let block_hash_bytes = <[u8; 32]>::try_from(block_hash_bytes)
+        .map_err(|_| anyhow::anyhow!("Block hash must be 32 bytes!"))?;
+
+    Ok(block_hash_bytes.into())
+}
+
+fn parse_block_id(block: &str) -> anyhow::Result<nagara_simple_wallet::BlockId> {
+    if block.starts_with("0x") {
+        Ok(parse_block_hash(block)?.into())
+    } else {
+        Ok(block.parse::<u32>()?.into())
+    }
+}
+
+impl Cli {
     async fn run() -> anyhow::Result<()> {
         nagara_logging::init();
 
-        let command = <Self as clap::Parser>::parse();
-        let mut instance = nagara_simple_wallet::WalletInstance::create_with_default_url().await?;
+        let cli = <Self as clap::Parser>::parse();
 
-        match command {
-            Self::Check { account } => {
-                let balance = instance.check_balance(&account).await?;
-                let balance_decimal =
-                    <bigdecimal::BigDecimal as bigdecimal::FromPrimitive>::from_u128(balance)
-                        .unwrap();
-                let balance_decimal = std::ops::Div::div(balance_decimal, get_decimal_scaler());
+        if let Commands::Keystore {
+            keystore_file,
+            command,
+        } = cli.command
+        {
+            return Self::run_keystore(keystore_file, command);
+        }
+
+        let mut network = cli
+            .network
+            .to_config()
+            .with_dynamic_fallback(cli.allow_dynamic);
+
+        if !cli.rpc_url.is_empty() {
+            network = network.with_rpc_urls(&cli.rpc_url);
+        }
+
+        if network.rpc_urls.is_empty() {
+            anyhow::bail!(
+                "Network {} has no public endpoint, use --rpc-url!",
+                network.name
+            );
+        }
+
+        if let Some(genesis_hash) = &cli.genesis_hash {
+            network = network.with_genesis_hash(parse_block_hash(genesis_hash)?);
+        }
+
+        if let (Some(spec_version), Some(transaction_version)) =
+            (cli.spec_version, cli.transaction_version)
+        {
+            network = network.with_runtime_versions(spec_version, transaction_version);
+        }
+
+        let mut instance =
+            nagara_simple_wallet::WalletInstance::create_with_network(network.clone()).await?;
+
+        if let Some(policy_file) = &cli.policy_file {
+            instance.set_spending_policies(nagara_simple_wallet::SpendingPolicies::load(
+                policy_file,
+            )?)?;
+        }
+
+        if let Some(spending_file) = &cli.spending_file {
+            instance.set_spending_file(spending_file)?;
+        }
+
+        if let Some(journal_file) = &cli.journal_file {
+            instance.set_journal(nagara_simple_wallet::TransactionJournal::open(
+                journal_file,
+            )?);
 
-                nagara_logging::info!("Balance is:\n\n{balance_decimal} NGR");
+            for entry in instance.recover_journal().await? {
+                nagara_logging::info!(
+                    "Journaled transaction {} (0x{}) is {}",
+                    entry.idempotency_key,
+                    hex::encode(entry.extrinsic_hash),
+                    serde_json::to_string(&entry.status)?,
+                );
             }
-            Self::Transfer {
+        }
+
+        let symbol = &network.token_symbol;
+
+        match cli.command {
+            Commands::Check {
+                account,
+                at_block,
+                at_time,
+            } => {
+                let balance = match (at_block, at_time) {
+                    (Some(at_block), _) => {
+                        instance
+                            .check_balance_at(&account, parse_block_id(&at_block)?)
+                            .await?
+                    }
+                    (None, Some(at_time)) => {
+                        let timestamp_millis =
+                            at_time.duration_since(std::time::UNIX_EPOCH)?.as_millis() as u64;
+
+                        instance
+                            .check_balance_at_timestamp(&account, timestamp_millis)
+                            .await?
+                    }
+                    (None, None) => instance.check_balance(&account).await?,
+                };
+                let balance_decimal = to_decimal(balance, &network)?;
+
+                nagara_logging::info!("Balance is:\n\n{balance_decimal} {symbol}");
+            }
+            Commands::Transfer {
                 private_key,
                 recipient,
                 amount,
                 edward,
+                idempotency_key,
+                tx_args,
             } => {
                 let schnorrkel = !edward;
                 let sender_address = instance.add_account(&private_key, schnorrkel)?;
@@ -59,15 +558,512 @@ impl Commands {
                     "Sending from {sender_address} to {recipient} with the amount of {amount} ({})",
                     if schnorrkel { "sr25519" } else { "ed25519" }
                 );
-                let amount_decimal = std::ops::Mul::mul(amount, get_decimal_scaler());
-                let amount = bigdecimal::ToPrimitive::to_u128(&amount_decimal)
-                    .ok_or(anyhow::anyhow!("Bad digits!"))?;
+                let amount = to_smallest_unit(amount, &network)?;
+                let options = tx_args.to_options(&network)?;
+
+                if let Some(idempotency_key) = idempotency_key {
+                    let entry = instance
+                        .submit_transfer_idempotent(
+                            &idempotency_key,
+                            &sender_address,
+                            &recipient,
+                            amount,
+                            &options,
+                        )
+                        .await?;
+
+                    while let Some(nagara_simple_wallet::TransactionStatus::Pending) =
+                        instance.transaction_status(entry.extrinsic_hash)
+                    {
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }
+
+                    let status = instance
+                        .transaction_status(entry.extrinsic_hash)
+                        .map_or(entry.status, Into::into);
+
+                    match status {
+                        nagara_simple_wallet::JournalStatus::InBlock { block_hash }
+                        | nagara_simple_wallet::JournalStatus::Finalized { block_hash, .. } => {
+                            nagara_logging::info!(
+                                "Transaction 0x{} was included, info:\n\n{}",
+                                hex::encode(entry.extrinsic_hash),
+                                instance.block_url(block_hash),
+                            )
+                        }
+                        status => nagara_logging::info!(
+                            "Transaction 0x{} is {}",
+                            hex::encode(entry.extrinsic_hash),
+                            serde_json::to_string(&status)?,
+                        ),
+                    }
+                } else {
+                    let explorer_url = instance
+                        .transfer_with_options(&sender_address, &recipient, amount, &options)
+                        .await?;
+
+                    nagara_logging::info!("Transaction was successful, info:\n\n{explorer_url}");
+                }
+            }
+            Commands::Tx {
+                private_key,
+                edward,
+                pallet,
+                call,
+                args,
+                tx_args,
+            } => {
+                let sender_address = instance.add_account(&private_key, !edward)?;
+                let json_args = serde_json::from_str::<serde_json::Value>(&args)?;
+                nagara_logging::info!("Submitting {pallet}::{call} from {sender_address}");
+                let options = tx_args.to_options(&network)?;
                 let explorer_url = instance
-                    .transfer(&sender_address, &recipient, amount)
+                    .submit_call_with_options(&sender_address, &pallet, &call, &json_args, &options)
                     .await?;
 
                 nagara_logging::info!("Transaction was successful, info:\n\n{explorer_url}");
             }
+            Commands::Storage {
+                pallet,
+                entry,
+                keys,
+                at_block,
+            } => {
+                let keys_json = serde_json::from_str::<serde_json::Value>(&keys)?;
+                let at_block = at_block.as_deref().map(parse_block_id).transpose()?;
+                let value = instance
+                    .query_storage(&pallet, &entry, &keys_json, at_block)
+                    .await?;
+
+                nagara_logging::info!(
+                    "{pallet}::{entry} is:\n\n{}",
+                    serde_json::to_string_pretty(&value)?
+                );
+            }
+            Commands::Constant { pallet, name } => {
+                let value = instance.query_constant(&pallet, &name)?;
+
+                nagara_logging::info!(
+                    "{pallet}::{name} is:\n\n{}",
+                    serde_json::to_string_pretty(&value)?
+                );
+            }
+            Commands::Watch { account, best } => {
+                let mut balance_changes = Box::pin(instance.watch_balance(&account, !best).await?);
+
+                while let Some(balance_info) = futures::StreamExt::next(&mut balance_changes).await
+                {
+                    let balance_info = balance_info?;
+                    let total_decimal = to_decimal(balance_info.total(), &network)?;
+                    let free_decimal = to_decimal(balance_info.free, &network)?;
+                    let reserved_decimal = to_decimal(balance_info.reserved, &network)?;
+                    let frozen_decimal = to_decimal(balance_info.frozen, &network)?;
+
+                    nagara_logging::info!(
+                        "Block #{} balance is {total_decimal} {symbol} (free {free_decimal}, reserved {reserved_decimal}, frozen {frozen_decimal})",
+                        balance_info.block_number
+                    );
+                }
+            }
+            Commands::Deposits {
+                account,
+                accounts_file,
+                cursor_file,
+                start_block,
+                min_confirmations,
+                index_db,
+            } => {
+                let mut scanner = nagara_simple_wallet::DepositScanner::new()
+                    .min_confirmations(min_confirmations);
+
+                if let Some(cursor_file) = cursor_file {
+                    scanner = scanner.cursor_file(cursor_file);
+                }
+
+                if let Some(start_block) = start_block {
+                    scanner = scanner.start_block(start_block);
+                }
+
+                let accounts_from_file = match accounts_file {
+                    Some(accounts_file) => std::fs::read_to_string(accounts_file)?,
+                    None => String::new(),
+                };
+                let history_index = match index_db {
+                    Some(index_db) => Some(nagara_simple_wallet::HistoryIndex::open(index_db)?),
+                    None => None,
+                };
+
+                for address in account
+                    .iter()
+                    .map(String::as_str)
+                    .chain(accounts_from_file.lines().map(str::trim))
+                    .filter(|address| !address.is_empty())
+                {
+                    scanner.watch_address(address)?;
+
+                    if let Some(history_index) = &history_index {
+                        history_index.watch_address(address)?;
+                    }
+                }
+
+                if let Some(history_index) = history_index {
+                    instance.start_history_index(history_index, start_block);
+                }
+
+                let mut deposits = Box::pin(instance.scan_deposits(scanner).await?);
+
+                while let Some(deposit) = futures::StreamExt::next(&mut deposits).await {
+                    let deposit = deposit?;
+                    let asset = match deposit.asset_id {
+                        Some(asset_id) => format!("of asset {asset_id}"),
+                        None => symbol.to_string(),
+                    };
+                    let amount = match deposit.asset_id {
+                        Some(_) => deposit.amount.to_string(),
+                        None => to_decimal(deposit.amount, &network)?.to_string(),
+                    };
+
+                    nagara_logging::info!(
+                        "Block #{} event {}: {} received {amount} {asset} from {} ({} confirmations)",
+                        deposit.block_number,
+                        deposit.event_index,
+                        deposit.recipient_address,
+                        deposit.sender_address,
+                        deposit.confirmations,
+                    );
+                }
+            }
+            Commands::History {
+                account,
+                from_block,
+                to_block,
+                index_file,
+            } => {
+                let entries = match index_file {
+                    Some(index_file) => {
+                        let (ledger, new_entries) = instance
+                            .sync_ledger_file(&account, &index_file, from_block.unwrap_or_default())
+                            .await?;
+                        nagara_logging::info!("Indexed {new_entries} new movements");
+
+                        ledger
+                            .entries
+                            .into_iter()
+                            .filter(|entry| {
+                                from_block
+                                    .map_or(true, |from_block| entry.block_number >= from_block)
+                                    && to_block
+                                        .map_or(true, |to_block| entry.block_number <= to_block)
+                            })
+                            .collect()
+                    }
+                    None => {
+                        let from_block = from_block.ok_or(anyhow::anyhow!(
+                            "First block is required without an index file!"
+                        ))?;
+                        let to_block = match to_block {
+                            Some(to_block) => to_block,
+                            None => instance.latest_block(true).await?,
+                        };
+
+                        instance
+                            .account_history(&account, from_block, to_block)
+                            .await?
+                    }
+                };
+                let mut history_info = String::new();
+
+                for entry in entries {
+                    let time = humantime::format_rfc3339_seconds(
+                        std::time::UNIX_EPOCH + std::time::Duration::from_millis(entry.timestamp),
+                    );
+                    let amount = match entry.kind {
+                        nagara_simple_wallet::LedgerKind::AssetTransfer { asset_id } => {
+                            format!("{} of asset {asset_id}", entry.amount)
+                        }
+                        _ => format!("{} {symbol}", to_decimal(entry.amount, &network)?),
+                    };
+                    history_info.push_str(&format!(
+                        "\n#{} {time} {} {:?} {} {} {amount} fee {} {symbol} {:?}",
+                        entry.block_number,
+                        entry.call.as_deref().unwrap_or("-"),
+                        entry.kind,
+                        if entry.incoming { "from" } else { "to" },
+                        entry.counterparty.as_deref().unwrap_or("-"),
+                        to_decimal(entry.fee, &network)?,
+                        entry.status,
+                    ));
+                }
+
+                nagara_logging::info!("History of {account}:\n{history_info}");
+            }
+            Commands::Report {
+                account,
+                accounts_file,
+                keys_file,
+                edward,
+                from,
+                to,
+                period,
+                format,
+                output,
+                totals_output,
+            } => {
+                if let Some(keys_file) = keys_file {
+                    let keys = std::fs::read_to_string(keys_file)?;
+
+                    for key in keys.lines().map(str::trim).filter(|key| !key.is_empty()) {
+                        instance.add_account(key, !edward)?;
+                    }
+                }
+
+                let wallet_accounts = instance.account_addresses();
+                let accounts_from_file = match accounts_file {
+                    Some(accounts_file) => std::fs::read_to_string(accounts_file)?,
+                    None => String::new(),
+                };
+                let accounts = account
+                    .iter()
+                    .chain(&wallet_accounts)
+                    .map(String::as_str)
+                    .chain(accounts_from_file.lines().map(str::trim))
+                    .filter(|address| !address.is_empty())
+                    .collect::<Vec<_>>();
+                let from_timestamp = from.duration_since(std::time::UNIX_EPOCH)?.as_millis() as u64;
+                let to_timestamp = to
+                    .unwrap_or_else(std::time::SystemTime::now)
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_millis() as u64;
+                let report = instance
+                    .accounting_report(
+                        &accounts,
+                        from_timestamp,
+                        to_timestamp,
+                        period.to_report_period(),
+                    )
+                    .await?;
+                let (report_text, totals_text) = match format {
+                    ReportFormat::Csv => (report.rows_to_csv(), Some(report.totals_to_csv())),
+                    ReportFormat::Json => (report.to_json()?, None),
+                };
+
+                match output {
+                    Some(output) => std::fs::write(output, report_text)?,
+                    None => nagara_logging::info!("Report:\n\n{report_text}"),
+                }
+
+                match (totals_text, totals_output) {
+                    (Some(totals_text), Some(totals_output)) => {
+                        std::fs::write(totals_output, totals_text)?
+                    }
+                    (Some(totals_text), None) => nagara_logging::info!("Totals:\n\n{totals_text}"),
+                    (None, _) => {}
+                }
+
+                nagara_logging::info!(
+                    "Reported {} movements of {} accounts",
+                    report.rows.len(),
+                    accounts.len()
+                );
+            }
+            Commands::Block {
+                command: BlockCommands::Show { at_block },
+            } => {
+                let block = match at_block {
+                    Some(at_block) => parse_block_id(&at_block)?,
+                    None => instance.latest_block(false).await?.into(),
+                };
+                let block_info = instance.block_info(block).await?;
+
+                nagara_logging::info!(
+                    "Block #{} has {} extrinsics:\n\n{}",
+                    block_info.number,
+                    block_info.extrinsic_count(),
+                    serde_json::to_string_pretty(&block_info)?
+                );
+            }
+            Commands::Extrinsic {
+                command: ExtrinsicCommands::Show { at_block, index },
+            } => {
+                let extrinsic_info = instance
+                    .extrinsic_info(parse_block_id(&at_block)?, index)
+                    .await?;
+
+                nagara_logging::info!(
+                    "Extrinsic {at_block}-{index} is:\n\n{}",
+                    serde_json::to_string_pretty(&extrinsic_info)?
+                );
+            }
+            Commands::Extrinsic {
+                command: ExtrinsicCommands::Decode { hex: extrinsic_hex },
+            } => {
+                let decoded_extrinsic = instance.decode_extrinsic(&extrinsic_hex)?;
+
+                nagara_logging::info!(
+                    "Extrinsic 0x{} is:\n\n{}",
+                    hex::encode(decoded_extrinsic.hash),
+                    serde_json::to_string_pretty(&decoded_extrinsic)?
+                );
+            }
+            Commands::Status { monitor, max_lag } => {
+                let chain_status = instance.chain_status().await?;
+
+                nagara_logging::info!(
+                    "Chain status:\n\n{}",
+                    serde_json::to_string_pretty(&chain_status)?
+                );
+
+                if monitor {
+                    let mut alerts = Box::pin(instance.watch_finality(max_lag).await?);
+
+                    while let Some(alert) = futures::StreamExt::next(&mut alerts).await {
+                        match alert? {
+                            nagara_simple_wallet::FinalityAlert::Stalled {
+                                best_number,
+                                finalized_number,
+                            } => nagara_logging::info!(
+                                "Finality stalled at block #{finalized_number}, best block is #{best_number}"
+                            ),
+                            nagara_simple_wallet::FinalityAlert::Recovered {
+                                best_number,
+                                finalized_number,
+                            } => nagara_logging::info!(
+                                "Finality recovered at block #{finalized_number}, best block is #{best_number}"
+                            ),
+                        }
+                    }
+                }
+            }
+            Commands::Pending { account } => {
+                let pending_transactions = instance.pending_transactions(&account).await?;
+                let mut pending_info = String::new();
+
+                for pending_transaction in pending_transactions {
+                    let tip_decimal = to_decimal(pending_transaction.tip, &network)?;
+                    pending_info.push_str(&format!(
+                        "\nnonce {} tip {tip_decimal} {symbol} hash 0x{}",
+                        pending_transaction.nonce,
+                        hex::encode(pending_transaction.extrinsic_hash),
+                    ));
+                }
+
+                nagara_logging::info!("Pending transactions:\n{pending_info}");
+            }
+            Commands::Replace {
+                private_key,
+                edward,
+                cancel,
+                tx_args,
+            } => {
+                let sender_address = instance.add_account(&private_key, !edward)?;
+                let nonce = tx_args.nonce.ok_or(anyhow::anyhow!(
+                    "Nonce of the pending transaction is required!"
+                ))?;
+                let options = tx_args.to_options(&network)?;
+                let outcome = if cancel {
+                    instance
+                        .cancel_transaction(&sender_address, nonce, &options)
+                        .await?
+                } else {
+                    instance
+                        .replace_transaction(&sender_address, nonce, &options)
+                        .await?
+                };
+
+                match outcome {
+                    nagara_simple_wallet::ReplacementOutcome::Replacement {
+                        extrinsic_hash,
+                        block_hash,
+                    } => nagara_logging::info!(
+                        "Replacement 0x{} was included, info:\n\n{}",
+                        hex::encode(extrinsic_hash),
+                        instance.block_url(block_hash),
+                    ),
+                    nagara_simple_wallet::ReplacementOutcome::Original { extrinsic_hash } => {
+                        nagara_logging::info!(
+                            "Original transaction 0x{} was included before the replacement",
+                            hex::encode(extrinsic_hash),
+                        )
+                    }
+                }
+            }
+            Commands::Serve {
+                listen,
+                api_token,
+                keystore_file,
+                passphrase,
+                keys_file,
+                edward,
+            } => {
+                if api_token.is_empty() {
+                    return Err(anyhow::anyhow!("API token must not be empty!"));
+                }
+
+                if let Some(keystore_file) = keystore_file {
+                    let passphrase = passphrase
+                        .ok_or(anyhow::anyhow!("Passphrase of the keystore is required!"))?;
+                    let keystore = nagara_simple_wallet::Keystore::open(keystore_file)?;
+
+                    for account_address in instance.unlock_keystore(&keystore, &passphrase)? {
+                        nagara_logging::info!("Paying from {account_address}");
+                    }
+                }
+
+                if let Some(keys_file) = keys_file {
+                    let keys = std::fs::read_to_string(keys_file)?;
+
+                    for key in keys.lines().map(str::trim).filter(|key| !key.is_empty()) {
+                        let account_address = instance.add_account(key, !edward)?;
+                        nagara_logging::info!("Paying from {account_address}");
+                    }
+                }
+
+                serve::serve(
+                    serve::ServeState {
+                        instance,
+                        api_token,
+                    },
+                    listen,
+                )
+                .await?;
+            }
+            Commands::Keystore { .. } => unreachable!("Keystore commands run offline"),
+        }
+
+        Ok(())
+    }
+
+    fn run_keystore(
+        keystore_file: std::path::PathBuf,
+        command: KeystoreCommands,
+    ) -> anyhow::Result<()> {
+        let mut keystore = nagara_simple_wallet::Keystore::open(keystore_file)?;
+
+        match command {
+            KeystoreCommands::Add {
+                private_key,
+                edward,
+                passphrase,
+            } => {
+                if passphrase.is_empty() {
+                    return Err(anyhow::anyhow!("Passphrase must not be empty!"));
+                }
+
+                let account_address = keystore.add(&private_key, !edward, &passphrase)?;
+                nagara_logging::info!("Added {account_address} to the keystore");
+            }
+            KeystoreCommands::List => {
+                for entry in keystore.entries() {
+                    nagara_logging::info!("{}", entry.address);
+                }
+            }
+            KeystoreCommands::Remove { account } => {
+                if !keystore.remove(&account)? {
+                    return Err(anyhow::anyhow!("{account} is not in the keystore!"));
+                }
+
+                nagara_logging::info!("Removed {account} from the keystore");
+            }
         }
 
         Ok(())
@@ -76,5 +1072,5 @@ impl Commands {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    Commands::run().await
+    Cli::run().await
 }
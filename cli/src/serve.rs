@@ -0,0 +1,815 @@
+//! HTTP API of the `serve` command, keeping the keys of the paying accounts out of the services
+//! calling it.
+//!
+//! Every route but `/openapi.json` requires the `Authorization: Bearer <API_TOKEN>` header.
+//! With a journal, transfers and payouts sent with an `Idempotency-Key` header are sent once per
+//! key.
+//! Amounts are NGR decimal strings. Errors are `{"code": ..., "message": ...}` bodies, the code
+//! being the one of the wallet error or `null` for errors of the API itself.
+//!
+//! `POST /v1/rpc` serves the same calls over JSON-RPC 2.0, the idempotency key being a param.
+
+pub(crate) struct ServeState {
+    pub(crate) instance: nagara_simple_wallet::WalletInstance,
+    pub(crate) api_token: String,
+}
+
+#[derive(serde::Serialize)]
+struct ApiError {
+    #[serde(skip)]
+    status: axum::http::StatusCode,
+    code: Option<i32>,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: axum::http::StatusCode, message: String) -> Self {
+        Self {
+            status,
+            code: None,
+            message,
+        }
+    }
+
+    /// Error of the JSON-RPC endpoint itself, carrying its JSON-RPC code.
+    fn rpc(status: axum::http::StatusCode, code: i32, message: String) -> Self {
+        Self {
+            status,
+            code: Some(code),
+            message,
+        }
+    }
+}
+
+impl From<nagara_simple_wallet::Error> for ApiError {
+    fn from(err: nagara_simple_wallet::Error) -> Self {
+        let status = match &err {
+            nagara_simple_wallet::Error::InvalidArgument(_) => axum::http::StatusCode::BAD_REQUEST,
+            nagara_simple_wallet::Error::AccountNotFound
+            | nagara_simple_wallet::Error::BlockNotFound
+            | nagara_simple_wallet::Error::ExtrinsicNotFound
+            | nagara_simple_wallet::Error::TransactionNotPending => {
+                axum::http::StatusCode::NOT_FOUND
+            }
+            nagara_simple_wallet::Error::InvalidTransaction(_)
+            | nagara_simple_wallet::Error::UnknownTransaction(_)
+            | nagara_simple_wallet::Error::ReplacementUnderpriced
+            | nagara_simple_wallet::Error::ReplacementDropped => {
+                axum::http::StatusCode::UNPROCESSABLE_ENTITY
+            }
+            nagara_simple_wallet::Error::PolicyViolation(_) => axum::http::StatusCode::FORBIDDEN,
+            nagara_simple_wallet::Error::SubxtError(_)
+            | nagara_simple_wallet::Error::NoHealthyEndpoint => axum::http::StatusCode::BAD_GATEWAY,
+            _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let message = err.to_string();
+
+        Self {
+            status,
+            code: Some(i32::from(err)),
+            message,
+        }
+    }
+}
+
+impl axum::response::IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        (self.status, axum::Json(&self)).into_response()
+    }
+}
+
+type ApiResult = Result<axum::Json<serde_json::Value>, ApiError>;
+
+#[derive(serde::Deserialize)]
+struct FeeArgs {
+    tip: Option<bigdecimal::BigDecimal>,
+    era_period: Option<u64>,
+}
+
+impl FeeArgs {
+    fn to_options(
+        &self,
+        network: &nagara_simple_wallet::NetworkConfig,
+    ) -> Result<nagara_simple_wallet::TxOptions, ApiError> {
+        let mut options = nagara_simple_wallet::TxOptions::new();
+
+        if let Some(tip) = &self.tip {
+            options = options.tip(to_smallest_unit(tip, network)?);
+        }
+
+        if let Some(era_period) = self.era_period {
+            options = options.mortal(era_period);
+        }
+
+        Ok(options)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct TransferRequest {
+    sender_address: String,
+    recipient_address: String,
+    amount: bigdecimal::BigDecimal,
+    #[serde(flatten)]
+    fee_args: FeeArgs,
+}
+
+#[derive(serde::Deserialize)]
+struct PayoutRequest {
+    recipient_address: String,
+    amount: bigdecimal::BigDecimal,
+}
+
+#[derive(serde::Deserialize)]
+struct PayoutsRequest {
+    sender_address: String,
+    payouts: Vec<PayoutRequest>,
+    #[serde(flatten)]
+    fee_args: FeeArgs,
+}
+
+impl PayoutsRequest {
+    fn to_payouts(
+        &self,
+        network: &nagara_simple_wallet::NetworkConfig,
+    ) -> Result<Vec<nagara_simple_wallet::Payout>, ApiError> {
+        self.payouts
+            .iter()
+            .map(|payout| {
+                Ok(nagara_simple_wallet::Payout {
+                    recipient_address: payout.recipient_address.clone(),
+                    amount: to_smallest_unit(&payout.amount, network)?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn to_smallest_unit(
+    amount: &bigdecimal::BigDecimal,
+    network: &nagara_simple_wallet::NetworkConfig,
+) -> Result<u128, ApiError> {
+    crate::to_smallest_unit(amount.clone(), network).map_err(|err| {
+        ApiError::new(
+            axum::http::StatusCode::BAD_REQUEST,
+            format!("Invalid amount {amount}: {err}"),
+        )
+    })
+}
+
+fn idempotency_key(headers: &axum::http::HeaderMap) -> Result<Option<&str>, ApiError> {
+    headers
+        .get("idempotency-key")
+        .map(|value| {
+            value.to_str().map_err(|_| {
+                ApiError::new(
+                    axum::http::StatusCode::BAD_REQUEST,
+                    "Idempotency key must be visible ASCII".to_string(),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn submitted(entry: nagara_simple_wallet::JournalEntry) -> axum::Json<serde_json::Value> {
+    axum::Json(serde_json::json!({
+        "extrinsic_hash": entry.extrinsic_hash,
+        "idempotency_key": entry.idempotency_key,
+        "status": entry.status,
+    }))
+}
+
+fn to_decimal(
+    amount: u128,
+    network: &nagara_simple_wallet::NetworkConfig,
+) -> Result<String, ApiError> {
+    crate::to_decimal(amount, network)
+        .map(|amount| amount.to_string())
+        .map_err(|err| {
+            ApiError::new(
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                err.to_string(),
+            )
+        })
+}
+
+/// Compare without stopping at the first different byte, so that the time taken does not tell how
+/// much of the token was guessed.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |difference, (left, right)| difference | (left ^ right))
+            == 0
+}
+
+async fn authorize<B>(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    request: axum::http::Request<B>,
+    next: axum::middleware::Next<B>,
+) -> Result<axum::response::Response, ApiError> {
+    let authorized = request
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map_or(false, |token| {
+            constant_time_eq(token.as_bytes(), state.api_token.as_bytes())
+        });
+
+    if !authorized {
+        return Err(ApiError::new(
+            axum::http::StatusCode::UNAUTHORIZED,
+            "Missing or wrong bearer token".to_string(),
+        ));
+    }
+
+    Ok(next.run(request).await)
+}
+
+async fn balance(state: &ServeState, address: String) -> ApiResult {
+    let network = state.instance.network();
+    let balance_info = state.instance.balance_info(&address).await?;
+
+    Ok(axum::Json(serde_json::json!({
+        "address": address,
+        "block_number": balance_info.block_number,
+        "block_hash": balance_info.block_hash,
+        "total": to_decimal(balance_info.total(), network)?,
+        "free": to_decimal(balance_info.free, network)?,
+        "reserved": to_decimal(balance_info.reserved, network)?,
+        "frozen": to_decimal(balance_info.frozen, network)?,
+    })))
+}
+
+async fn transfer(
+    state: &ServeState,
+    idempotency_key: Option<&str>,
+    request: TransferRequest,
+) -> ApiResult {
+    let network = state.instance.network();
+    let amount = to_smallest_unit(&request.amount, network)?;
+    let options = request.fee_args.to_options(network)?;
+
+    if let Some(idempotency_key) = idempotency_key {
+        let entry = state
+            .instance
+            .submit_transfer_idempotent(
+                idempotency_key,
+                &request.sender_address,
+                &request.recipient_address,
+                amount,
+                &options,
+            )
+            .await?;
+
+        return Ok(submitted(entry));
+    }
+
+    let extrinsic_hash = state
+        .instance
+        .submit_transfer(
+            &request.sender_address,
+            &request.recipient_address,
+            amount,
+            &options,
+        )
+        .await?;
+
+    Ok(axum::Json(
+        serde_json::json!({ "extrinsic_hash": extrinsic_hash }),
+    ))
+}
+
+async fn payouts(
+    state: &ServeState,
+    idempotency_key: Option<&str>,
+    request: PayoutsRequest,
+) -> ApiResult {
+    let network = state.instance.network();
+    let payouts = request.to_payouts(network)?;
+    let options = request.fee_args.to_options(network)?;
+
+    if let Some(idempotency_key) = idempotency_key {
+        let entry = state
+            .instance
+            .submit_payouts_idempotent(idempotency_key, &request.sender_address, &payouts, &options)
+            .await?;
+
+        return Ok(submitted(entry));
+    }
+
+    let extrinsic_hash = state
+        .instance
+        .submit_payouts(&request.sender_address, &payouts, &options)
+        .await?;
+
+    Ok(axum::Json(
+        serde_json::json!({ "extrinsic_hash": extrinsic_hash }),
+    ))
+}
+
+async fn transfer_fee(state: &ServeState, request: TransferRequest) -> ApiResult {
+    let network = state.instance.network();
+    let amount = to_smallest_unit(&request.amount, network)?;
+    let options = request.fee_args.to_options(network)?;
+    let fee = state
+        .instance
+        .estimate_transfer_fee(
+            &request.sender_address,
+            &request.recipient_address,
+            amount,
+            &options,
+        )
+        .await?;
+
+    Ok(axum::Json(
+        serde_json::json!({ "fee": to_decimal(fee, network)? }),
+    ))
+}
+
+async fn payouts_fee(state: &ServeState, request: PayoutsRequest) -> ApiResult {
+    let network = state.instance.network();
+    let payouts = request.to_payouts(network)?;
+    let options = request.fee_args.to_options(network)?;
+    let fee = state
+        .instance
+        .estimate_payouts_fee(&request.sender_address, &payouts, &options)
+        .await?;
+
+    Ok(axum::Json(
+        serde_json::json!({ "fee": to_decimal(fee, network)? }),
+    ))
+}
+
+fn transaction(state: &ServeState, extrinsic_hash: &str) -> ApiResult {
+    let extrinsic_hash = crate::parse_block_hash(extrinsic_hash).map_err(|err| {
+        ApiError::new(
+            axum::http::StatusCode::BAD_REQUEST,
+            format!("Invalid extrinsic hash: {err}"),
+        )
+    })?;
+    let status = match state.instance.transaction_status(extrinsic_hash) {
+        Some(status) => Some(nagara_simple_wallet::JournalStatus::from(status)),
+        None => state
+            .instance
+            .journal_entries()
+            .into_iter()
+            .find(|entry| entry.extrinsic_hash == extrinsic_hash)
+            .map(|entry| entry.status),
+    }
+    .ok_or_else(|| {
+        ApiError::new(
+            axum::http::StatusCode::NOT_FOUND,
+            "Transaction was not submitted through this daemon".to_string(),
+        )
+    })?;
+
+    Ok(axum::Json(serde_json::json!({
+        "extrinsic_hash": extrinsic_hash,
+        "status": status,
+    })))
+}
+
+async fn get_balance(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    axum::extract::Path(address): axum::extract::Path<String>,
+) -> ApiResult {
+    balance(&state, address).await
+}
+
+async fn post_transfer(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    headers: axum::http::HeaderMap,
+    axum::Json(request): axum::Json<TransferRequest>,
+) -> ApiResult {
+    transfer(&state, idempotency_key(&headers)?, request).await
+}
+
+async fn post_payouts(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    headers: axum::http::HeaderMap,
+    axum::Json(request): axum::Json<PayoutsRequest>,
+) -> ApiResult {
+    payouts(&state, idempotency_key(&headers)?, request).await
+}
+
+async fn post_transfer_fee(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    axum::Json(request): axum::Json<TransferRequest>,
+) -> ApiResult {
+    transfer_fee(&state, request).await
+}
+
+async fn post_payouts_fee(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    axum::Json(request): axum::Json<PayoutsRequest>,
+) -> ApiResult {
+    payouts_fee(&state, request).await
+}
+
+async fn get_transaction(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    axum::extract::Path(extrinsic_hash): axum::extract::Path<String>,
+) -> ApiResult {
+    transaction(&state, &extrinsic_hash)
+}
+
+#[derive(serde::Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: serde_json::Value,
+    method: String,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+#[derive(serde::Deserialize)]
+struct AddressParams {
+    address: String,
+}
+
+#[derive(serde::Deserialize)]
+struct HashParams {
+    extrinsic_hash: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Idempotent<T> {
+    idempotency_key: Option<String>,
+    #[serde(flatten)]
+    request: T,
+}
+
+const RPC_PARSE_ERROR: i32 = -32700;
+const RPC_INVALID_REQUEST: i32 = -32600;
+const RPC_METHOD_NOT_FOUND: i32 = -32601;
+const RPC_INVALID_PARAMS: i32 = -32602;
+const RPC_SERVER_ERROR: i32 = -32000;
+
+/// JSON-RPC 2.0 error of a failed call, `code` is the wallet error code when there is one.
+fn rpc_error(id: serde_json::Value, err: ApiError) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": err.code.unwrap_or(RPC_SERVER_ERROR),
+            "message": err.message,
+            "data": { "status": err.status.as_u16() },
+        },
+    })
+}
+
+fn rpc_params<T: serde::de::DeserializeOwned>(params: serde_json::Value) -> Result<T, ApiError> {
+    serde_json::from_value(params).map_err(|err| {
+        ApiError::rpc(
+            axum::http::StatusCode::BAD_REQUEST,
+            RPC_INVALID_PARAMS,
+            format!("Invalid params: {err}"),
+        )
+    })
+}
+
+async fn rpc_call(state: &ServeState, method: &str, params: serde_json::Value) -> ApiResult {
+    match method {
+        "wallet_balance" => {
+            let params = rpc_params::<AddressParams>(params)?;
+
+            balance(state, params.address).await
+        }
+        "wallet_transfer" => {
+            let params = rpc_params::<Idempotent<TransferRequest>>(params)?;
+
+            transfer(state, params.idempotency_key.as_deref(), params.request).await
+        }
+        "wallet_payouts" => {
+            let params = rpc_params::<Idempotent<PayoutsRequest>>(params)?;
+
+            payouts(state, params.idempotency_key.as_deref(), params.request).await
+        }
+        "wallet_transferFee" => transfer_fee(state, rpc_params(params)?).await,
+        "wallet_payoutsFee" => payouts_fee(state, rpc_params(params)?).await,
+        "wallet_transaction" => {
+            let params = rpc_params::<HashParams>(params)?;
+
+            transaction(state, &params.extrinsic_hash)
+        }
+        _ => Err(ApiError::rpc(
+            axum::http::StatusCode::NOT_FOUND,
+            RPC_METHOD_NOT_FOUND,
+            format!("Method {method} not found"),
+        )),
+    }
+}
+
+/// JSON-RPC 2.0 endpoint with the methods of the REST routes, errors are in the response body.
+async fn post_rpc(
+    axum::extract::State(state): axum::extract::State<std::sync::Arc<ServeState>>,
+    body: axum::body::Bytes,
+) -> axum::Json<serde_json::Value> {
+    let request = match serde_json::from_slice::<RpcRequest>(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            return axum::Json(rpc_error(
+                serde_json::Value::Null,
+                ApiError::rpc(
+                    axum::http::StatusCode::BAD_REQUEST,
+                    RPC_PARSE_ERROR,
+                    format!("Invalid request: {err}"),
+                ),
+            ))
+        }
+    };
+
+    if request.jsonrpc != "2.0" {
+        return axum::Json(rpc_error(
+            request.id,
+            ApiError::rpc(
+                axum::http::StatusCode::BAD_REQUEST,
+                RPC_INVALID_REQUEST,
+                "Only JSON-RPC 2.0 is served".to_string(),
+            ),
+        ));
+    }
+
+    let response = match rpc_call(&state, &request.method, request.params).await {
+        Ok(axum::Json(result)) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request.id,
+            "result": result,
+        }),
+        Err(err) => rpc_error(request.id, err),
+    };
+
+    axum::Json(response)
+}
+
+async fn get_openapi() -> axum::Json<serde_json::Value> {
+    axum::Json(openapi())
+}
+
+fn openapi() -> serde_json::Value {
+    let error = serde_json::json!({ "$ref": "#/components/responses/Error" });
+    let errors = serde_json::json!({
+        "400": error,
+        "401": error,
+        "403": error,
+        "404": error,
+        "422": error,
+        "500": error,
+        "502": error,
+    });
+    let idempotency_key = serde_json::json!({
+        "name": "Idempotency-Key",
+        "in": "header",
+        "required": false,
+        "description": "Send the payment once per key, the transaction already sent under the key is returned. Needs the daemon to run with --journal-file",
+        "schema": { "type": "string" },
+    });
+    let json_body = |schema: &str| {
+        serde_json::json!({
+            "required": true,
+            "content": { "application/json": { "schema": { "$ref": format!("#/components/schemas/{schema}") } } },
+        })
+    };
+    let json_response = |description: &str, schema: &str| {
+        serde_json::json!({
+            "description": description,
+            "content": { "application/json": { "schema": { "$ref": format!("#/components/schemas/{schema}") } } },
+        })
+    };
+    let with_errors = |response: serde_json::Value| {
+        let mut responses = errors.clone();
+        responses["200"] = response;
+
+        responses
+    };
+
+    serde_json::json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "nagara simple wallet daemon",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Amounts are NGR decimal strings. Transactions are submitted without waiting for their inclusion, follow them with GET /v1/transactions/{hash}.",
+        },
+        "security": [{ "bearer": [] }],
+        "paths": {
+            "/v1/balances/{address}": {
+                "get": {
+                    "summary": "Balances of an account at the latest block",
+                    "parameters": [{ "name": "address", "in": "path", "required": true, "schema": { "type": "string" } }],
+                    "responses": with_errors(json_response("Balances", "Balance")),
+                },
+            },
+            "/v1/transfers": {
+                "post": {
+                    "summary": "Submit a transfer from an account of the daemon",
+                    "parameters": [idempotency_key],
+                    "requestBody": json_body("TransferRequest"),
+                    "responses": with_errors(json_response("Submitted transaction", "Submitted")),
+                },
+            },
+            "/v1/payouts": {
+                "post": {
+                    "summary": "Submit transfers to several recipients in one batch, all of them succeed or none",
+                    "parameters": [idempotency_key],
+                    "requestBody": json_body("PayoutsRequest"),
+                    "responses": with_errors(json_response("Submitted transaction", "Submitted")),
+                },
+            },
+            "/v1/fees/transfer": {
+                "post": {
+                    "summary": "Estimate the fee of a transfer, tip excluded",
+                    "requestBody": json_body("TransferRequest"),
+                    "responses": with_errors(json_response("Fee estimate", "Fee")),
+                },
+            },
+            "/v1/fees/payouts": {
+                "post": {
+                    "summary": "Estimate the fee of a batch payout, tip excluded",
+                    "requestBody": json_body("PayoutsRequest"),
+                    "responses": with_errors(json_response("Fee estimate", "Fee")),
+                },
+            },
+            "/v1/transactions/{hash}": {
+                "get": {
+                    "summary": "Status of a transaction submitted through the daemon",
+                    "parameters": [{ "name": "hash", "in": "path", "required": true, "schema": { "type": "string" } }],
+                    "responses": with_errors(json_response("Transaction status", "Transaction")),
+                },
+            },
+            "/v1/rpc": {
+                "post": {
+                    "summary": "JSON-RPC 2.0 calls, one per request",
+                    "description": "Methods and params: wallet_balance {address}, wallet_transfer TransferRequest with an optional idempotency_key, wallet_payouts PayoutsRequest with an optional idempotency_key, wallet_transferFee TransferRequest, wallet_payoutsFee PayoutsRequest, wallet_transaction {extrinsic_hash}. Results are the bodies of the matching REST routes. Error codes are the wallet error codes, the JSON-RPC ones for malformed calls and -32000 for the other errors of the API, the matching HTTP status being in data.status.",
+                    "requestBody": json_body("RpcRequest"),
+                    "responses": {
+                        "200": json_response("JSON-RPC response, a result or an error", "RpcResponse"),
+                        "401": error,
+                    },
+                },
+            },
+            "/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "security": [],
+                    "responses": { "200": { "description": "OpenAPI document" } },
+                },
+            },
+        },
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer" },
+            },
+            "responses": {
+                "Error": json_response("Error", "Error"),
+            },
+            "schemas": {
+                "Decimal": { "type": "string", "example": "1.5" },
+                "Balance": {
+                    "type": "object",
+                    "properties": {
+                        "address": { "type": "string" },
+                        "block_number": { "type": "integer" },
+                        "block_hash": { "type": "string" },
+                        "total": { "$ref": "#/components/schemas/Decimal" },
+                        "free": { "$ref": "#/components/schemas/Decimal" },
+                        "reserved": { "$ref": "#/components/schemas/Decimal" },
+                        "frozen": { "$ref": "#/components/schemas/Decimal" },
+                    },
+                },
+                "TransferRequest": {
+                    "type": "object",
+                    "required": ["sender_address", "recipient_address", "amount"],
+                    "properties": {
+                        "sender_address": { "type": "string" },
+                        "recipient_address": { "type": "string" },
+                        "amount": { "$ref": "#/components/schemas/Decimal" },
+                        "tip": { "$ref": "#/components/schemas/Decimal" },
+                        "era_period": { "type": "integer", "description": "Mortal for this many blocks, immortal if omitted" },
+                    },
+                },
+                "PayoutsRequest": {
+                    "type": "object",
+                    "required": ["sender_address", "payouts"],
+                    "properties": {
+                        "sender_address": { "type": "string" },
+                        "payouts": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["recipient_address", "amount"],
+                                "properties": {
+                                    "recipient_address": { "type": "string" },
+                                    "amount": { "$ref": "#/components/schemas/Decimal" },
+                                },
+                            },
+                        },
+                        "tip": { "$ref": "#/components/schemas/Decimal" },
+                        "era_period": { "type": "integer", "description": "Mortal for this many blocks, immortal if omitted" },
+                    },
+                },
+                "Submitted": {
+                    "type": "object",
+                    "properties": {
+                        "extrinsic_hash": { "type": "string" },
+                        "idempotency_key": { "type": "string", "description": "Only with an Idempotency-Key header" },
+                        "status": { "$ref": "#/components/schemas/Status" },
+                    },
+                },
+                "Fee": {
+                    "type": "object",
+                    "properties": { "fee": { "$ref": "#/components/schemas/Decimal" } },
+                },
+                "Transaction": {
+                    "type": "object",
+                    "properties": {
+                        "extrinsic_hash": { "type": "string" },
+                        "status": { "$ref": "#/components/schemas/Status" },
+                    },
+                },
+                "Status": {
+                    "type": "object",
+                    "properties": {
+                        "status": {
+                            "type": "string",
+                            "enum": ["signed", "pending", "in_block", "finalized", "failed"],
+                            "description": "Signed when a journaled transaction may not have reached the node yet",
+                        },
+                        "block_hash": { "type": "string" },
+                        "success": { "type": "boolean" },
+                        "reason": { "type": "string" },
+                    },
+                },
+                "RpcRequest": {
+                    "type": "object",
+                    "required": ["jsonrpc", "method"],
+                    "properties": {
+                        "jsonrpc": { "type": "string", "enum": ["2.0"] },
+                        "id": {},
+                        "method": { "type": "string" },
+                        "params": { "type": "object" },
+                    },
+                },
+                "RpcResponse": {
+                    "type": "object",
+                    "properties": {
+                        "jsonrpc": { "type": "string", "enum": ["2.0"] },
+                        "id": {},
+                        "result": {},
+                        "error": {
+                            "type": "object",
+                            "properties": {
+                                "code": { "type": "integer" },
+                                "message": { "type": "string" },
+                                "data": { "type": "object", "properties": { "status": { "type": "integer" } } },
+                            },
+                        },
+                    },
+                },
+                "Error": {
+                    "type": "object",
+                    "properties": {
+                        "code": { "type": "integer", "nullable": true, "description": "Code of the wallet error" },
+                        "message": { "type": "string" },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// Serve the API on `listen` until ctrl-c, the accounts paying must already be in the instance.
+pub(crate) async fn serve(state: ServeState, listen: std::net::SocketAddr) -> anyhow::Result<()> {
+    let state = std::sync::Arc::new(state);
+    let router = axum::Router::new()
+        .route("/v1/balances/:address", axum::routing::get(get_balance))
+        .route("/v1/transfers", axum::routing::post(post_transfer))
+        .route("/v1/payouts", axum::routing::post(post_payouts))
+        .route("/v1/fees/transfer", axum::routing::post(post_transfer_fee))
+        .route("/v1/fees/payouts", axum::routing::post(post_payouts_fee))
+        .route(
+            "/v1/transactions/:hash",
+            axum::routing::get(get_transaction),
+        )
+        .route("/v1/rpc", axum::routing::post(post_rpc))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            authorize,
+        ))
+        .route("/openapi.json", axum::routing::get(get_openapi))
+        .with_state(state);
+
+    nagara_logging::info!("Serving the wallet API on http://{listen}");
+
+    axum::Server::bind(&listen)
+        .serve(router.into_make_service())
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+/// Balances of an account at the end of a block.
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub struct BalanceInfo {
+    pub block_number: u32,
+    pub block_hash: crate::BlockHash,
+    pub free: u128,
+    pub reserved: u128,
+    pub frozen: u128,
+}
+
+impl BalanceInfo {
+    pub fn total(&self) -> u128 {
+        self.free + self.reserved + self.frozen
+    }
+
+    fn same_balances(&self, other: &Self) -> bool {
+        (self.free, self.reserved, self.frozen) == (other.free, other.reserved, other.frozen)
+    }
+}
+
+struct BalanceWatch {
+    blocks: core::pin::Pin<
+        Box<
+            dyn futures::Stream<
+                    Item = Result<
+                        subxt::blocks::Block<
+                            subxt::PolkadotConfig,
+                            subxt::OnlineClient<subxt::PolkadotConfig>,
+                        >,
+                        subxt::Error,
+                    >,
+                > + Send,
+        >,
+    >,
+    client: subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: std::sync::Arc<std::sync::RwLock<crate::RuntimeMode>>,
+    account: subxt::utils::AccountId32,
+    last: Option<BalanceInfo>,
+}
+
+/// Read `system.account` at the block through the checked-in or live metadata.
+pub(crate) async fn fetch_balance_info(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: crate::RuntimeMode,
+    account: &subxt::utils::AccountId32,
+    block_number: u32,
+    block_hash: crate::BlockHash,
+) -> crate::Result<BalanceInfo> {
+    let storage = client.storage().at(block_hash);
+    let mut balance_info = BalanceInfo {
+        block_number,
+        block_hash,
+        free: 0,
+        reserved: 0,
+        frozen: 0,
+    };
+
+    if runtime_mode == crate::RuntimeMode::Dynamic {
+        let data_pointer = subxt::dynamic::storage(
+            "System",
+            "Account",
+            vec![subxt::dynamic::Value::from_bytes(account.0)],
+        );
+
+        if let Some(account_info) = storage.fetch(&data_pointer).await? {
+            let account_info = account_info.to_value()?;
+            let data_field = |field: &str| {
+                subxt::dynamic::At::at(&account_info, "data")
+                    .and_then(|data| subxt::dynamic::At::at(data, field))
+                    .and_then(|value| value.as_u128())
+                    .unwrap_or_default()
+            };
+            balance_info.free = data_field("free");
+            balance_info.reserved = data_field("reserved");
+            balance_info.frozen = data_field("frozen");
+        }
+
+        return Ok(balance_info);
+    }
+
+    let data_pointer = crate::metadata::nagara::api::storage()
+        .system()
+        .account(account);
+
+    if let Some(account_info) = storage.fetch(&data_pointer).await? {
+        balance_info.free = account_info.data.free;
+        balance_info.reserved = account_info.data.reserved;
+        balance_info.frozen = account_info.data.frozen;
+    }
+
+    Ok(balance_info)
+}
+
+impl crate::WalletInstance {
+    pub async fn balance_info(&self, account_address: &str) -> crate::Result<BalanceInfo> {
+        let account = crate::compat::parse_account(account_address)?;
+        let chain_heads = self.chain_heads();
+
+        fetch_balance_info(
+            &self.client,
+            self.compatible_runtime_mode()?,
+            &account,
+            chain_heads.best_number(),
+            chain_heads.best_hash,
+        )
+        .await
+    }
+
+    /// Follow the balances of the account, a new item comes every time they change.
+    ///
+    /// The first item holds the balances at subscription time. Following best blocks reports
+    /// changes sooner, but they may be reverted by a reorg.
+    pub async fn watch_balance(
+        &self,
+        account_address: &str,
+        finalized: bool,
+    ) -> crate::Result<impl futures::Stream<Item = crate::Result<BalanceInfo>> + Send + 'static>
+    {
+        let account = crate::compat::parse_account(account_address)?;
+        let blocks_client = self.client.blocks();
+        let blocks = if finalized {
+            blocks_client.subscribe_finalized().await?
+        } else {
+            blocks_client.subscribe_best().await?
+        };
+        let watch = BalanceWatch {
+            blocks,
+            client: self.client.clone(),
+            runtime_mode: self.runtime_mode.clone(),
+            account,
+            last: None,
+        };
+
+        Ok(futures::stream::unfold(watch, |mut watch| async move {
+            loop {
+                let balance_info = match futures::StreamExt::next(&mut watch.blocks).await? {
+                    Ok(block) => {
+                        let runtime_mode = crate::compat::compatible_runtime_mode(
+                            &watch.client,
+                            &watch.runtime_mode,
+                        );
+
+                        match runtime_mode {
+                            Ok(runtime_mode) => {
+                                fetch_balance_info(
+                                    &watch.client,
+                                    runtime_mode,
+                                    &watch.account,
+                                    block.number(),
+                                    block.hash(),
+                                )
+                                .await
+                            }
+                            Err(err) => Err(err),
+                        }
+                    }
+                    Err(err) => Err(err.into()),
+                };
+
+                match balance_info {
+                    Ok(balance_info)
+                        if watch
+                            .last
+                            .map_or(false, |last| last.same_balances(&balance_info)) =>
+                    {
+                        continue
+                    }
+                    Ok(balance_info) => {
+                        watch.last = Some(balance_info);
+
+                        return Some((Ok(balance_info), watch));
+                    }
+                    Err(err) => return Some((Err(err), watch)),
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,7 @@
+fn main() -> std::io::Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "nagara_simple_wallet.h".to_string());
+
+    nagara_simple_wallet::generate_headers(path)
+}
@@ -0,0 +1,224 @@
+type SignerAddress = subxt::utils::MultiAddress<subxt::utils::AccountId32, ()>;
+
+/// A block with its extrinsics and events decoded through live metadata.
+#[derive(Clone, core::fmt::Debug, PartialEq, serde::Serialize)]
+pub struct BlockInfo {
+    pub number: u32,
+    pub hash: crate::BlockHash,
+    pub parent_hash: crate::BlockHash,
+    pub state_root: crate::BlockHash,
+    pub extrinsics_root: crate::BlockHash,
+    /// Aura authority of the slot the block was produced in, `None` for the genesis block.
+    pub author: Option<String>,
+    /// Unix timestamp of the block in milliseconds.
+    pub timestamp: u64,
+    pub extrinsics: Vec<ExtrinsicInfo>,
+    /// Events of block initialization and finalization, the others belong to their extrinsic.
+    pub events: Vec<EventInfo>,
+}
+
+#[derive(Clone, core::fmt::Debug, PartialEq, serde::Serialize)]
+pub struct ExtrinsicInfo {
+    pub index: u32,
+    pub hash: crate::ExtrinsicHash,
+    /// SS58 address of the signer, `None` for unsigned extrinsics.
+    pub signer: Option<String>,
+    pub pallet: String,
+    pub call: String,
+    pub args: serde_json::Value,
+    /// `None` when the block has no `ExtrinsicSuccess` or `ExtrinsicFailed` event for it.
+    pub success: Option<bool>,
+    pub events: Vec<EventInfo>,
+}
+
+#[derive(Clone, core::fmt::Debug, PartialEq, serde::Serialize)]
+pub struct EventInfo {
+    pub index: u32,
+    /// `None` for events of block initialization and finalization.
+    pub extrinsic_index: Option<u32>,
+    pub pallet: String,
+    pub variant: String,
+    pub fields: serde_json::Value,
+}
+
+impl BlockInfo {
+    pub fn extrinsic_count(&self) -> usize {
+        self.extrinsics.len()
+    }
+}
+
+/// Slot of the block from its Aura pre-runtime digest.
+fn aura_slot(
+    header: &<subxt::PolkadotConfig as subxt::Config>::Header,
+) -> crate::Result<Option<u64>> {
+    for log in &header.digest.logs {
+        if let subxt::config::substrate::DigestItem::PreRuntime(engine_id, slot) = log {
+            if engine_id == b"aura" {
+                let slot = <u64 as parity_scale_codec::Decode>::decode(&mut slot.as_slice())
+                    .map_err(|err| {
+                        crate::Error::OtherError(format!("Malformed Aura digest: {err}"))
+                    })?;
+
+                return Ok(Some(slot));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+impl crate::WalletInstance {
+    /// Header, author, timestamp, extrinsics and events of the block.
+    ///
+    /// Extrinsics and events are decoded with the metadata of the current runtime, blocks of an
+    /// older runtime may fail to decode.
+    pub async fn block_info(&self, block: crate::BlockId) -> crate::Result<BlockInfo> {
+        let block_hash = self.block_hash(block).await?;
+        let block = self.client.blocks().at(block_hash).await?;
+        let header = block.header();
+        let runtime_mode = self.compatible_runtime_mode()?;
+        let metadata = self.client.metadata();
+        let types = metadata.types();
+        let mut events = Vec::new();
+
+        for event in block.events().await?.iter() {
+            let event = event?;
+            events.push(EventInfo {
+                index: event.index(),
+                extrinsic_index: crate::events::extrinsic_index(&event),
+                pallet: event.pallet_name().to_string(),
+                variant: event.variant_name().to_string(),
+                fields: crate::dynamic::composite_to_json(
+                    types,
+                    self.network.ss58_prefix,
+                    &event.field_values()?,
+                ),
+            });
+        }
+
+        let mut extrinsics = Vec::new();
+
+        for extrinsic in block.extrinsics().await?.iter() {
+            let extrinsic = extrinsic?;
+            let signer = extrinsic.address_bytes().map(|address_bytes| {
+                match <SignerAddress as parity_scale_codec::Decode>::decode(&mut &address_bytes[..])
+                {
+                    Ok(subxt::utils::MultiAddress::Id(account)) => {
+                        self.network.encode_address(&account)
+                    }
+                    _ => format!("0x{}", hex::encode(address_bytes)),
+                }
+            });
+            let extrinsic_events = events
+                .iter()
+                .filter(|event| event.extrinsic_index == Some(extrinsic.index()))
+                .cloned()
+                .collect::<Vec<_>>();
+            let success = extrinsic_events.iter().find_map(|event| {
+                match (event.pallet.as_str(), event.variant.as_str()) {
+                    ("System", "ExtrinsicSuccess") => Some(true),
+                    ("System", "ExtrinsicFailed") => Some(false),
+                    _ => None,
+                }
+            });
+            extrinsics.push(ExtrinsicInfo {
+                index: extrinsic.index(),
+                hash: <subxt::config::substrate::BlakeTwo256 as subxt::config::Hasher>::hash_of(
+                    &extrinsic.bytes(),
+                ),
+                signer,
+                pallet: extrinsic.pallet_name()?.to_string(),
+                call: extrinsic.variant_name()?.to_string(),
+                args: crate::dynamic::composite_to_json(
+                    types,
+                    self.network.ss58_prefix,
+                    &extrinsic.field_values()?,
+                ),
+                success,
+                events: extrinsic_events,
+            });
+        }
+
+        events.retain(|event| event.extrinsic_index.is_none());
+
+        let author = match aura_slot(header)? {
+            Some(slot) => {
+                let authorities = self
+                    .aura_authorities(runtime_mode, header.parent_hash)
+                    .await?;
+
+                match authorities.len() {
+                    0 => None,
+                    authority_count => {
+                        let author = &authorities[(slot % authority_count as u64) as usize];
+
+                        Some(author.to_string())
+                    }
+                }
+            }
+            None => None,
+        };
+
+        Ok(BlockInfo {
+            number: header.number,
+            hash: block_hash,
+            parent_hash: header.parent_hash,
+            state_root: header.state_root,
+            extrinsics_root: header.extrinsics_root,
+            author,
+            timestamp: crate::historical::fetch_timestamp(&self.client, runtime_mode, block_hash)
+                .await?,
+            extrinsics,
+            events,
+        })
+    }
+
+    /// One extrinsic of the block, see [`Self::block_info`].
+    pub async fn extrinsic_info(
+        &self,
+        block: crate::BlockId,
+        extrinsic_index: u32,
+    ) -> crate::Result<ExtrinsicInfo> {
+        self.block_info(block)
+            .await?
+            .extrinsics
+            .into_iter()
+            .find(|extrinsic| extrinsic.index == extrinsic_index)
+            .ok_or(crate::Error::ExtrinsicNotFound)
+    }
+
+    pub(crate) async fn aura_authorities(
+        &self,
+        runtime_mode: crate::RuntimeMode,
+        block_hash: crate::BlockHash,
+    ) -> crate::Result<Vec<subxt::utils::AccountId32>> {
+        let storage = self.client.storage().at(block_hash);
+
+        if runtime_mode == crate::RuntimeMode::Dynamic {
+            let data_pointer = subxt::dynamic::storage_root("Aura", "Authorities");
+            let authorities = storage
+                .fetch_raw(&data_pointer.to_root_bytes())
+                .await?
+                .unwrap_or_default();
+            let authorities =
+                <Vec<[u8; 32]> as parity_scale_codec::Decode>::decode(&mut authorities.as_slice())
+                    .map_err(|err| {
+                        crate::Error::OtherError(format!("Malformed Aura authorities: {err}"))
+                    })?;
+
+            return Ok(authorities
+                .into_iter()
+                .map(subxt::utils::AccountId32)
+                .collect());
+        }
+
+        let data_pointer = crate::metadata::nagara::api::storage().aura().authorities();
+        let authorities = storage.fetch_or_default(&data_pointer).await?;
+
+        Ok(authorities
+            .0
+            .into_iter()
+            .map(|authority| subxt::utils::AccountId32(authority.0 .0))
+            .collect())
+    }
+}
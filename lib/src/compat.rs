@@ -0,0 +1,171 @@
+/// How the wallet encodes calls and decodes storage for the connected runtime.
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq)]
+pub enum RuntimeMode {
+    /// The checked-in `metadata/nagara.rs` matches the runtime.
+    Static,
+    /// The runtime diverged from the checked-in metadata, everything goes through live metadata.
+    Dynamic,
+    /// A runtime upgrade made the runtime diverge and dynamic fallback is not allowed.
+    Incompatible,
+}
+
+/// Check the node is on the expected chain and decide how to talk to its runtime.
+pub(crate) fn verify_chain(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+    network: &crate::NetworkConfig,
+) -> crate::Result<RuntimeMode> {
+    let genesis_hash = client.genesis_hash();
+
+    match network.genesis_hash {
+        Some(expected_genesis_hash) if expected_genesis_hash != genesis_hash => {
+            return Err(crate::Error::GenesisMismatch {
+                expected: expected_genesis_hash,
+                actual: genesis_hash,
+            });
+        }
+        Some(_) => {}
+        // A dev chain gets a new genesis every time it is started.
+        None if network.name == crate::NetworkConfig::local().name => {}
+        // Built-in profiles aren't pinned yet, refusing them would break every existing caller.
+        None => nagara_logging::info!(
+            "Network {} has no expected genesis hash, the node's 0x{} is trusted unchecked, pin it with `with_genesis_hash`",
+            network.name,
+            hex::encode(genesis_hash)
+        ),
+    }
+
+    let runtime_version = client.runtime_version();
+    let spec_version_matches = network.spec_version.map_or(true, |spec_version| {
+        spec_version == runtime_version.spec_version
+    });
+    let transaction_version_matches = network
+        .transaction_version
+        .map_or(true, |transaction_version| {
+            transaction_version == runtime_version.transaction_version
+        });
+    let codegen_valid = crate::metadata::nagara::api::is_codegen_valid_for(&client.metadata());
+
+    if spec_version_matches && transaction_version_matches && codegen_valid {
+        Ok(RuntimeMode::Static)
+    } else if network.allow_dynamic_fallback {
+        nagara_logging::info!(
+            "Runtime {}/{} does not match the built-in metadata, using dynamic mode",
+            runtime_version.spec_version,
+            runtime_version.transaction_version
+        );
+
+        Ok(RuntimeMode::Dynamic)
+    } else {
+        Err(crate::Error::IncompatibleRuntime {
+            spec_version: runtime_version.spec_version,
+            transaction_version: runtime_version.transaction_version,
+        })
+    }
+}
+
+/// Current runtime mode, failing with `IncompatibleRuntime` when the runtime cannot be used.
+pub(crate) fn compatible_runtime_mode(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: &std::sync::RwLock<RuntimeMode>,
+) -> crate::Result<RuntimeMode> {
+    match *runtime_mode.read().unwrap() {
+        RuntimeMode::Incompatible => {
+            let runtime_version = client.runtime_version();
+
+            Err(crate::Error::IncompatibleRuntime {
+                spec_version: runtime_version.spec_version,
+                transaction_version: runtime_version.transaction_version,
+            })
+        }
+        runtime_mode => Ok(runtime_mode),
+    }
+}
+
+pub(crate) fn dynamic_transfer_keep_alive(
+    recipient_account: subxt::utils::AccountId32,
+    balance: u128,
+) -> subxt::tx::DynamicPayload {
+    subxt::dynamic::tx(
+        "Balances",
+        "transfer_keep_alive",
+        vec![
+            subxt::dynamic::Value::unnamed_variant(
+                "Id",
+                [subxt::dynamic::Value::from_bytes(recipient_account.0)],
+            ),
+            subxt::dynamic::Value::u128(balance),
+        ],
+    )
+}
+
+/// `Balances::transfer_keep_alive` as a `RuntimeCall` value, to nest it in another call.
+pub(crate) fn dynamic_transfer_keep_alive_call(
+    recipient_account: subxt::utils::AccountId32,
+    balance: u128,
+) -> subxt::dynamic::Value {
+    subxt::dynamic::Value::unnamed_variant(
+        "Balances",
+        [subxt::dynamic::Value::unnamed_variant(
+            "transfer_keep_alive",
+            [
+                subxt::dynamic::Value::unnamed_variant(
+                    "Id",
+                    [subxt::dynamic::Value::from_bytes(recipient_account.0)],
+                ),
+                subxt::dynamic::Value::u128(balance),
+            ],
+        )],
+    )
+}
+
+pub(crate) fn dynamic_batch_all(calls: Vec<subxt::dynamic::Value>) -> subxt::tx::DynamicPayload {
+    subxt::dynamic::tx(
+        "Utility",
+        "batch_all",
+        vec![subxt::dynamic::Value::unnamed_composite(calls)],
+    )
+}
+
+pub(crate) fn dynamic_remark(remark: Vec<u8>) -> subxt::tx::DynamicPayload {
+    subxt::dynamic::tx(
+        "System",
+        "remark",
+        vec![subxt::dynamic::Value::from_bytes(remark)],
+    )
+}
+
+pub(crate) fn parse_account(address: &str) -> crate::Result<subxt::utils::AccountId32> {
+    <subxt::utils::AccountId32 as core::str::FromStr>::from_str(address)
+        .map_err(|_| crate::Error::InvalidArgument(format!("{address} is not an SS58 address")))
+}
+
+/// Account id of a dynamically decoded `AccountId32`, whatever newtypes wrap its 32 bytes.
+pub(crate) fn dynamic_account_id(
+    value: &subxt::dynamic::DecodedValue,
+) -> Option<subxt::utils::AccountId32> {
+    let composite = match &value.value {
+        subxt::ext::scale_value::ValueDef::Composite(composite) => composite,
+        _ => return None,
+    };
+
+    if composite.len() == 1 {
+        return dynamic_account_id(composite.values().next()?);
+    }
+
+    let account_bytes = composite
+        .values()
+        .map(|item| item.as_u128().and_then(|byte| u8::try_from(byte).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+
+    Some(subxt::utils::AccountId32(account_bytes.try_into().ok()?))
+}
+
+/// Integer of a dynamically decoded value, whatever newtypes wrap it.
+pub(crate) fn dynamic_u128(value: &subxt::dynamic::DecodedValue) -> Option<u128> {
+    match &value.value {
+        subxt::ext::scale_value::ValueDef::Composite(composite) if composite.len() == 1 => {
+            dynamic_u128(composite.values().next()?)
+        }
+        _ => value.as_u128(),
+    }
+}
@@ -0,0 +1,329 @@
+/// Transfer into a watched address, native token or asset.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq)]
+pub struct DepositRecord {
+    pub block_number: u32,
+    pub block_hash: crate::BlockHash,
+    /// `None` for transfers not made by an extrinsic, like scheduled ones.
+    pub extrinsic_index: Option<u32>,
+    pub event_index: u32,
+    /// `None` for the native token.
+    pub asset_id: Option<u32>,
+    pub sender_address: String,
+    /// The watched address, as it was given to the scanner.
+    pub recipient_address: String,
+    pub amount: u128,
+    /// Finalized blocks on top of the deposit block, itself included, when it was reported.
+    pub confirmations: u32,
+}
+
+/// Watched addresses and scanning settings of [`crate::WalletInstance::scan_deposits`].
+///
+/// Clones share the watched addresses, so addresses can be added while a scan is running.
+#[derive(Clone)]
+pub struct DepositScanner {
+    watched: std::sync::Arc<
+        std::sync::RwLock<std::collections::HashMap<subxt::utils::AccountId32, String>>,
+    >,
+    min_confirmations: u32,
+    cursor_path: Option<std::path::PathBuf>,
+    start_block: Option<u32>,
+}
+
+struct DepositScan {
+    client: subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: std::sync::Arc<std::sync::RwLock<crate::RuntimeMode>>,
+    scanner: DepositScanner,
+    ss58_prefix: u16,
+    #[cfg(feature = "index-db")]
+    history_index: Option<crate::HistoryIndex>,
+    head_updates: futures::channel::mpsc::UnboundedReceiver<crate::ChainHeads>,
+    finalized_number: u32,
+    next_block: u32,
+    unsaved_cursor: Option<u32>,
+    /// Deposits of the scanned blocks, with the errors of their undecodable events in order.
+    deposits: std::collections::VecDeque<crate::Result<DepositRecord>>,
+}
+
+impl DepositScanner {
+    pub const DEFAULT_MIN_CONFIRMATIONS: u32 = 1;
+
+    pub fn new() -> Self {
+        Self {
+            watched: std::sync::Arc::new(std::sync::RwLock::new(std::collections::HashMap::new())),
+            min_confirmations: Self::DEFAULT_MIN_CONFIRMATIONS,
+            cursor_path: None,
+            start_block: None,
+        }
+    }
+
+    pub fn watch_address(&self, address: &str) -> crate::Result<()> {
+        let account = <subxt::utils::AccountId32 as core::str::FromStr>::from_str(address)
+            .map_err(|_| {
+                crate::Error::InvalidArgument(format!("{address} is not an SS58 address"))
+            })?;
+        self.watched
+            .write()
+            .unwrap()
+            .insert(account, address.to_string());
+
+        Ok(())
+    }
+
+    pub fn unwatch_address(&self, address: &str) {
+        if let Ok(account) = <subxt::utils::AccountId32 as core::str::FromStr>::from_str(address) {
+            self.watched.write().unwrap().remove(&account);
+        }
+    }
+
+    /// Report deposits only once this many finalized blocks, the deposit block included, exist.
+    pub fn min_confirmations(mut self, min_confirmations: u32) -> Self {
+        self.min_confirmations = min_confirmations.max(1);
+
+        self
+    }
+
+    /// Remember the last scanned block in this file, a restarted scan resumes right after it.
+    pub fn cursor_file<P: core::convert::Into<std::path::PathBuf>>(
+        mut self,
+        cursor_path: P,
+    ) -> Self {
+        self.cursor_path = Some(cursor_path.into());
+
+        self
+    }
+
+    /// First block to scan when there is no cursor yet, defaults to the finalized head.
+    pub fn start_block(mut self, start_block: u32) -> Self {
+        self.start_block = Some(start_block);
+
+        self
+    }
+
+    /// Last scanned block read from the cursor file.
+    pub fn cursor(&self) -> crate::Result<Option<u32>> {
+        let cursor_path = match &self.cursor_path {
+            Some(cursor_path) if cursor_path.exists() => cursor_path,
+            _ => return Ok(None),
+        };
+        let cursor = std::fs::read_to_string(cursor_path)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        cursor
+            .trim()
+            .parse::<u32>()
+            .map(Some)
+            .map_err(|err| crate::Error::OtherError(format!("Corrupted deposit cursor: {err}")))
+    }
+
+    fn save_cursor(&self, cursor: u32) -> crate::Result<()> {
+        let cursor_path = match &self.cursor_path {
+            Some(cursor_path) => cursor_path,
+            None => return Ok(()),
+        };
+        let cursor_path_tmp = cursor_path.with_extension("tmp");
+
+        std::fs::write(&cursor_path_tmp, cursor.to_string())
+            .and_then(|_| std::fs::rename(&cursor_path_tmp, cursor_path))
+            .map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+
+    fn watched_address(&self, account: &subxt::utils::AccountId32) -> Option<String> {
+        self.watched.read().unwrap().get(account).cloned()
+    }
+}
+
+impl Default for DepositScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DepositScan {
+    async fn scan_next_block(&mut self) -> crate::Result<()> {
+        let block_number = self.next_block;
+
+        #[cfg(feature = "index-db")]
+        if self.scan_indexed_block(block_number)? {
+            return Ok(());
+        }
+
+        let block_hash = self
+            .client
+            .rpc()
+            .block_hash(Some(block_number.into()))
+            .await?
+            .ok_or(crate::Error::BlockNotFound)?;
+
+        let runtime_mode =
+            crate::compat::compatible_runtime_mode(&self.client, &self.runtime_mode)?;
+        let events = self.client.events().at(block_hash).await?;
+
+        for event in events.iter() {
+            // Decoding the same block again fails the same way, so an undecodable event is
+            // reported once and the scan moves on.
+            let decoded_event = event.map_err(crate::Error::from).and_then(|event| {
+                let wallet_event = crate::events::decode_event(&event, runtime_mode)?;
+
+                Ok((event, wallet_event))
+            });
+            let (event, asset_id, sender, recipient, amount) = match decoded_event {
+                Ok((
+                    event,
+                    Some(crate::events::WalletEvent::Transfer {
+                        asset_id,
+                        from,
+                        to,
+                        amount,
+                    }),
+                )) => (event, asset_id, from, to, amount),
+                Ok(_) => continue,
+                Err(err) => {
+                    nagara_logging::info!("Skipping an event of block #{block_number}: {err}");
+                    self.deposits.push_back(Err(err));
+
+                    continue;
+                }
+            };
+
+            if let Some(recipient_address) = self.scanner.watched_address(&recipient) {
+                self.deposits.push_back(Ok(DepositRecord {
+                    block_number,
+                    block_hash,
+                    extrinsic_index: crate::events::extrinsic_index(&event),
+                    event_index: event.index(),
+                    asset_id,
+                    sender_address: crate::network::encode_ss58(self.ss58_prefix, &sender),
+                    recipient_address,
+                    amount,
+                    confirmations: self.finalized_number - block_number + 1,
+                }));
+            }
+        }
+
+        self.next_block += 1;
+        self.unsaved_cursor = Some(block_number);
+
+        Ok(())
+    }
+
+    /// Read the deposits of the block from the history index, `false` when it doesn't cover them.
+    #[cfg(feature = "index-db")]
+    fn scan_indexed_block(&mut self, block_number: u32) -> crate::Result<bool> {
+        let history_index = match &self.history_index {
+            Some(history_index) => history_index,
+            None => return Ok(false),
+        };
+        let watched = self.scanner.watched.read().unwrap().clone();
+        let accounts = watched.keys().cloned().collect::<Vec<_>>();
+        let entries = match history_index.finalized_entries_at(&accounts, block_number)? {
+            Some(entries) => entries,
+            None => return Ok(false),
+        };
+        let mut deposits = entries
+            .into_iter()
+            .filter_map(|(account, entry)| {
+                let asset_id = match entry.kind {
+                    crate::LedgerKind::Transfer => None,
+                    crate::LedgerKind::AssetTransfer { asset_id } => Some(asset_id),
+                    _ => return None,
+                };
+
+                if !entry.incoming {
+                    return None;
+                }
+
+                Some(DepositRecord {
+                    block_number,
+                    block_hash: entry.block_hash,
+                    extrinsic_index: entry.extrinsic_index,
+                    event_index: entry.event_index?,
+                    asset_id,
+                    sender_address: entry.counterparty?,
+                    recipient_address: watched.get(&account)?.clone(),
+                    amount: entry.amount,
+                    confirmations: self.finalized_number - block_number + 1,
+                })
+            })
+            .collect::<Vec<_>>();
+        deposits.sort_by_key(|deposit| deposit.event_index);
+        self.deposits.extend(deposits.into_iter().map(Ok));
+        self.next_block += 1;
+        self.unsaved_cursor = Some(block_number);
+
+        Ok(true)
+    }
+
+    async fn next_deposit(&mut self) -> Option<crate::Result<DepositRecord>> {
+        loop {
+            if let Some(deposit) = self.deposits.pop_front() {
+                return Some(deposit);
+            }
+
+            if let Some(cursor) = self.unsaved_cursor.take() {
+                if let Err(err) = self.scanner.save_cursor(cursor) {
+                    return Some(Err(err));
+                }
+            }
+
+            let confirmed = self
+                .next_block
+                .saturating_add(self.scanner.min_confirmations - 1);
+
+            if confirmed <= self.finalized_number {
+                if let Err(err) = self.scan_next_block().await {
+                    self.deposits.clear();
+
+                    return Some(Err(err));
+                }
+
+                continue;
+            }
+
+            let chain_heads = futures::StreamExt::next(&mut self.head_updates).await?;
+            self.finalized_number = chain_heads.finalized_number();
+        }
+    }
+}
+
+impl crate::WalletInstance {
+    /// Report transfers into the watched addresses, block after block of the finalized chain.
+    ///
+    /// Blocks the history index covers for every watched address are read from it instead of the
+    /// chain.
+    ///
+    /// Resumes after the cursor of the scanner when it has one, the cursor only moves past a
+    /// block once all of its deposits were taken from the stream. An error ends the current block,
+    /// polling again retries it, except for an event that can't be decoded which is reported as an
+    /// error among the deposits of its block and skipped.
+    pub async fn scan_deposits(
+        &self,
+        scanner: DepositScanner,
+    ) -> crate::Result<impl futures::Stream<Item = crate::Result<DepositRecord>> + Send + 'static>
+    {
+        let head_updates = self.chain_head_updates();
+        let finalized_number = self.chain_heads().finalized_number();
+        let next_block = match scanner.cursor()? {
+            Some(cursor) => cursor + 1,
+            None => scanner.start_block.unwrap_or(finalized_number),
+        };
+        let scan = DepositScan {
+            client: self.client.clone(),
+            runtime_mode: self.runtime_mode.clone(),
+            scanner,
+            ss58_prefix: self.network.ss58_prefix,
+            #[cfg(feature = "index-db")]
+            history_index: self.history_index.clone(),
+            head_updates,
+            finalized_number,
+            next_block,
+            unsaved_cursor: None,
+            deposits: std::collections::VecDeque::new(),
+        };
+
+        Ok(futures::stream::unfold(scan, |mut scan| async move {
+            let deposit = scan.next_deposit().await?;
+
+            Some((deposit, scan))
+        }))
+    }
+}
@@ -0,0 +1,613 @@
+type PortableType = scale_info::Type<scale_info::form::PortableForm>;
+type PortableField = scale_info::Field<scale_info::form::PortableForm>;
+
+fn resolve_type(
+    types: &scale_info::PortableRegistry,
+    type_id: u32,
+) -> crate::Result<&PortableType> {
+    types
+        .resolve(type_id)
+        .ok_or_else(|| crate::Error::MetadataNotFound(format!("Type #{type_id}")))
+}
+
+fn type_name(ty: &PortableType, type_id: u32) -> String {
+    if ty.path.segments.is_empty() {
+        format!("type #{type_id}")
+    } else {
+        ty.path.segments.join("::")
+    }
+}
+
+fn is_byte(types: &scale_info::PortableRegistry, type_id: u32) -> bool {
+    matches!(
+        types.resolve(type_id).map(|ty| &ty.type_def),
+        Some(scale_info::TypeDef::Primitive(
+            scale_info::TypeDefPrimitive::U8
+        ))
+    )
+}
+
+/// Match metadata names loosely, so `transaction_payment` finds `TransactionPayment`.
+pub(crate) fn same_name(metadata_name: &str, name: &str) -> bool {
+    let normalize = |name: &str| name.replace('_', "").to_lowercase();
+
+    normalize(metadata_name) == normalize(name)
+}
+
+fn find_pallet<'a>(
+    metadata: &'a subxt::Metadata,
+    pallet_name: &str,
+) -> crate::Result<subxt::metadata::types::PalletMetadata<'a>> {
+    metadata
+        .pallets()
+        .find(|pallet| same_name(pallet.name(), pallet_name))
+        .ok_or_else(|| crate::Error::MetadataNotFound(format!("Pallet {pallet_name}")))
+}
+
+fn invalid_argument(json: &serde_json::Value, expected: &str) -> crate::Error {
+    crate::Error::InvalidArgument(format!("{json} is not a valid {expected}"))
+}
+
+/// Convert JSON into a value of the runtime type `type_id`, following the shape of the type.
+///
+/// Composites take an object keyed by field name or an array of the fields in order, variants take
+/// their name as a string or an object with their name as the only key. Numbers can be written as
+/// strings to go past the JSON precision, byte arrays take `0x` hex, 32 bytes also take an SS58
+/// address and byte sequences also take plain text. `null` is `None` and anything else is `Some`
+/// for options, a string is the `Id` of a `MultiAddress`.
+pub(crate) fn json_to_value(
+    types: &scale_info::PortableRegistry,
+    type_id: u32,
+    json: &serde_json::Value,
+) -> crate::Result<subxt::dynamic::Value> {
+    let ty = resolve_type(types, type_id)?;
+
+    match &ty.type_def {
+        scale_info::TypeDef::Composite(composite) => {
+            let fields = json_to_fields(types, &composite.fields, json)?;
+
+            Ok(subxt::dynamic::Value::without_context(
+                subxt::ext::scale_value::ValueDef::Composite(fields),
+            ))
+        }
+        scale_info::TypeDef::Variant(variant_type) => {
+            let find_variant = |name: &str| {
+                variant_type
+                    .variants
+                    .iter()
+                    .find(|variant| variant.name == name)
+            };
+            let (variant, fields_json) = match json {
+                serde_json::Value::String(name) if find_variant(name).is_some() => {
+                    (find_variant(name).unwrap(), &serde_json::Value::Null)
+                }
+                serde_json::Value::Object(object)
+                    if object.len() == 1
+                        && object.keys().all(|name| find_variant(name).is_some()) =>
+                {
+                    let (name, fields_json) = object.iter().next().unwrap();
+
+                    (find_variant(name).unwrap(), fields_json)
+                }
+                serde_json::Value::Null if find_variant("None").is_some() => {
+                    (find_variant("None").unwrap(), &serde_json::Value::Null)
+                }
+                _ if find_variant("Some").is_some() => (find_variant("Some").unwrap(), json),
+                serde_json::Value::String(_) if find_variant("Id").is_some() => {
+                    (find_variant("Id").unwrap(), json)
+                }
+                _ => return Err(invalid_argument(json, &type_name(ty, type_id))),
+            };
+            let fields = json_to_fields(types, &variant.fields, fields_json)?;
+
+            Ok(subxt::dynamic::Value::variant(variant.name.clone(), fields))
+        }
+        scale_info::TypeDef::Sequence(sequence) => match json {
+            serde_json::Value::String(text) if is_byte(types, sequence.type_param.id) => {
+                match text.strip_prefix("0x") {
+                    Some(text_hex) => hex::decode(text_hex)
+                        .map(subxt::dynamic::Value::from_bytes)
+                        .map_err(|_| invalid_argument(json, "hex string")),
+                    None => Ok(subxt::dynamic::Value::from_bytes(text.as_bytes())),
+                }
+            }
+            serde_json::Value::Array(items) => Ok(subxt::dynamic::Value::unnamed_composite(
+                items
+                    .iter()
+                    .map(|item| json_to_value(types, sequence.type_param.id, item))
+                    .collect::<crate::Result<Vec<_>>>()?,
+            )),
+            _ => Err(invalid_argument(json, "sequence")),
+        },
+        scale_info::TypeDef::Array(array) => match json {
+            serde_json::Value::String(text) if is_byte(types, array.type_param.id) => {
+                let bytes = match text.strip_prefix("0x") {
+                    Some(text_hex) => hex::decode(text_hex).ok(),
+                    None if array.len == 32 => {
+                        <subxt::utils::AccountId32 as core::str::FromStr>::from_str(text)
+                            .ok()
+                            .map(|account| account.0.to_vec())
+                    }
+                    None => None,
+                };
+
+                match bytes {
+                    Some(bytes) if bytes.len() == array.len as usize => {
+                        Ok(subxt::dynamic::Value::from_bytes(bytes))
+                    }
+                    _ => Err(invalid_argument(json, &format!("{} bytes", array.len))),
+                }
+            }
+            serde_json::Value::Array(items) if items.len() == array.len as usize => {
+                Ok(subxt::dynamic::Value::unnamed_composite(
+                    items
+                        .iter()
+                        .map(|item| json_to_value(types, array.type_param.id, item))
+                        .collect::<crate::Result<Vec<_>>>()?,
+                ))
+            }
+            _ => Err(invalid_argument(json, &format!("array of {}", array.len))),
+        },
+        scale_info::TypeDef::Tuple(tuple) => {
+            let items = match json {
+                serde_json::Value::Null if tuple.fields.is_empty() => Vec::new(),
+                serde_json::Value::Array(items) if items.len() == tuple.fields.len() => {
+                    items.iter().collect()
+                }
+                _ if tuple.fields.len() == 1 => vec![json],
+                _ => return Err(invalid_argument(json, "tuple")),
+            };
+
+            Ok(subxt::dynamic::Value::unnamed_composite(
+                tuple
+                    .fields
+                    .iter()
+                    .zip(items)
+                    .map(|(field, item)| json_to_value(types, field.id, item))
+                    .collect::<crate::Result<Vec<_>>>()?,
+            ))
+        }
+        scale_info::TypeDef::Primitive(primitive) => json_to_primitive(primitive, json),
+        scale_info::TypeDef::Compact(compact) => json_to_value(types, compact.type_param.id, json),
+        scale_info::TypeDef::BitSequence(_) => match json {
+            serde_json::Value::Array(items) => {
+                let bits = items
+                    .iter()
+                    .map(|item| item.as_bool().ok_or_else(|| invalid_argument(item, "bit")))
+                    .collect::<crate::Result<subxt::ext::scale_bits::Bits>>()?;
+
+                Ok(subxt::dynamic::Value::bit_sequence(bits))
+            }
+            _ => Err(invalid_argument(json, "bit sequence")),
+        },
+    }
+}
+
+fn json_to_fields(
+    types: &scale_info::PortableRegistry,
+    fields: &[PortableField],
+    json: &serde_json::Value,
+) -> crate::Result<subxt::ext::scale_value::Composite<()>> {
+    let named = fields.iter().all(|field| field.name.is_some());
+    let items: Vec<&serde_json::Value> = match json {
+        serde_json::Value::Null if fields.is_empty() => Vec::new(),
+        serde_json::Value::Object(object) if named && fields.len() != 1 => fields
+            .iter()
+            .map(|field| {
+                let name = field.name.as_deref().unwrap_or_default();
+
+                object.get(name).ok_or_else(|| {
+                    crate::Error::InvalidArgument(format!("Missing field {name} in {json}"))
+                })
+            })
+            .collect::<crate::Result<_>>()?,
+        serde_json::Value::Array(items) if items.len() == fields.len() && fields.len() != 1 => {
+            items.iter().collect()
+        }
+        serde_json::Value::Object(object)
+            if named
+                && object.len() == 1
+                && object.contains_key(fields[0].name.as_deref().unwrap_or_default()) =>
+        {
+            object.values().collect()
+        }
+        serde_json::Value::Array(items) if items.len() == 1 && fields.len() == 1 => {
+            items.iter().collect()
+        }
+        _ if fields.len() == 1 => vec![json],
+        _ => {
+            return Err(crate::Error::InvalidArgument(format!(
+                "{json} does not match the {} expected fields",
+                fields.len()
+            )))
+        }
+    };
+    let values = fields
+        .iter()
+        .zip(items)
+        .map(|(field, item)| json_to_value(types, field.ty.id, item))
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    if named && !fields.is_empty() {
+        Ok(subxt::ext::scale_value::Composite::named(
+            fields
+                .iter()
+                .map(|field| field.name.clone().unwrap_or_default())
+                .zip(values),
+        ))
+    } else {
+        Ok(subxt::ext::scale_value::Composite::unnamed(values))
+    }
+}
+
+fn json_to_primitive(
+    primitive: &scale_info::TypeDefPrimitive,
+    json: &serde_json::Value,
+) -> crate::Result<subxt::dynamic::Value> {
+    let as_u128 = || match json {
+        serde_json::Value::Number(number) => number.as_u64().map(u128::from),
+        serde_json::Value::String(text) => text.parse::<u128>().ok(),
+        _ => None,
+    };
+    let as_i128 = || match json {
+        serde_json::Value::Number(number) => number.as_i64().map(i128::from),
+        serde_json::Value::String(text) => text.parse::<i128>().ok(),
+        _ => None,
+    };
+
+    match primitive {
+        scale_info::TypeDefPrimitive::Bool => json
+            .as_bool()
+            .map(subxt::dynamic::Value::bool)
+            .ok_or_else(|| invalid_argument(json, "bool")),
+        scale_info::TypeDefPrimitive::Char => {
+            let mut chars = json.as_str().unwrap_or_default().chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(char), None) => Ok(subxt::dynamic::Value::char(char)),
+                _ => Err(invalid_argument(json, "char")),
+            }
+        }
+        scale_info::TypeDefPrimitive::Str => json
+            .as_str()
+            .map(subxt::dynamic::Value::string)
+            .ok_or_else(|| invalid_argument(json, "string")),
+        scale_info::TypeDefPrimitive::U8
+        | scale_info::TypeDefPrimitive::U16
+        | scale_info::TypeDefPrimitive::U32
+        | scale_info::TypeDefPrimitive::U64
+        | scale_info::TypeDefPrimitive::U128 => as_u128()
+            .map(subxt::dynamic::Value::u128)
+            .ok_or_else(|| invalid_argument(json, "unsigned integer")),
+        scale_info::TypeDefPrimitive::I8
+        | scale_info::TypeDefPrimitive::I16
+        | scale_info::TypeDefPrimitive::I32
+        | scale_info::TypeDefPrimitive::I64
+        | scale_info::TypeDefPrimitive::I128 => as_i128()
+            .map(subxt::dynamic::Value::i128)
+            .ok_or_else(|| invalid_argument(json, "integer")),
+        scale_info::TypeDefPrimitive::U256 | scale_info::TypeDefPrimitive::I256 => Err(
+            crate::Error::InvalidArgument("256-bit integers are not supported".to_string()),
+        ),
+    }
+}
+
+/// Convert a value decoded through live metadata into JSON, the way [`json_to_value`] reads it.
+///
+/// Byte arrays and sequences become `0x` hex, account ids become SS58 addresses, integers past the
+/// JSON precision become strings, `None` becomes `null` and `Some` its content.
+pub(crate) fn value_to_json(
+    types: &scale_info::PortableRegistry,
+    ss58_prefix: u16,
+    value: &subxt::dynamic::DecodedValue,
+) -> serde_json::Value {
+    let ty = types.resolve(value.context);
+
+    match &value.value {
+        subxt::ext::scale_value::ValueDef::Composite(composite) => {
+            let type_def = ty.map(|ty| &ty.type_def);
+            let is_account_id = ty.map_or(false, |ty| {
+                ty.path.segments.last().map(String::as_str) == Some("AccountId32")
+            });
+
+            match type_def {
+                Some(scale_info::TypeDef::Composite(_)) if is_account_id => {
+                    let account_bytes =
+                        composite
+                            .values()
+                            .next()
+                            .and_then(|item| match &item.value {
+                                subxt::ext::scale_value::ValueDef::Composite(bytes) => {
+                                    composite_bytes(types, bytes)
+                                }
+                                _ => None,
+                            });
+
+                    match account_bytes.map(<[u8; 32]>::try_from) {
+                        Some(Ok(account_bytes)) => {
+                            serde_json::Value::String(crate::network::encode_ss58(
+                                ss58_prefix,
+                                &subxt::utils::AccountId32(account_bytes),
+                            ))
+                        }
+                        _ => composite_to_json(types, ss58_prefix, composite),
+                    }
+                }
+                Some(scale_info::TypeDef::Composite(_)) if composite.len() == 1 => {
+                    match composite {
+                        subxt::ext::scale_value::Composite::Unnamed(items) => {
+                            value_to_json(types, ss58_prefix, &items[0])
+                        }
+                        _ => composite_to_json(types, ss58_prefix, composite),
+                    }
+                }
+                Some(scale_info::TypeDef::Sequence(_)) | Some(scale_info::TypeDef::Array(_))
+                    if !composite.is_empty() =>
+                {
+                    match composite_bytes(types, composite) {
+                        Some(bytes) => {
+                            serde_json::Value::String(format!("0x{}", hex::encode(bytes)))
+                        }
+                        None => composite_to_json(types, ss58_prefix, composite),
+                    }
+                }
+                _ => composite_to_json(types, ss58_prefix, composite),
+            }
+        }
+        subxt::ext::scale_value::ValueDef::Variant(variant) => {
+            match (variant.name.as_str(), &variant.values) {
+                ("None", values) if values.is_empty() => serde_json::Value::Null,
+                ("Some", subxt::ext::scale_value::Composite::Unnamed(items))
+                    if items.len() == 1 =>
+                {
+                    value_to_json(types, ss58_prefix, &items[0])
+                }
+                (name, values) if values.is_empty() => serde_json::Value::String(name.to_string()),
+                (name, subxt::ext::scale_value::Composite::Unnamed(items)) if items.len() == 1 => {
+                    serde_json::Value::Object(serde_json::Map::from_iter([(
+                        name.to_string(),
+                        value_to_json(types, ss58_prefix, &items[0]),
+                    )]))
+                }
+                (name, values) => serde_json::Value::Object(serde_json::Map::from_iter([(
+                    name.to_string(),
+                    composite_to_json(types, ss58_prefix, values),
+                )])),
+            }
+        }
+        subxt::ext::scale_value::ValueDef::BitSequence(bits) => {
+            serde_json::Value::Array(bits.iter().map(serde_json::Value::Bool).collect())
+        }
+        subxt::ext::scale_value::ValueDef::Primitive(primitive) => match primitive {
+            subxt::ext::scale_value::Primitive::Bool(value) => serde_json::Value::Bool(*value),
+            subxt::ext::scale_value::Primitive::Char(value) => {
+                serde_json::Value::String(value.to_string())
+            }
+            subxt::ext::scale_value::Primitive::String(value) => {
+                serde_json::Value::String(value.clone())
+            }
+            subxt::ext::scale_value::Primitive::U128(value) => match u64::try_from(*value) {
+                Ok(value) => serde_json::Value::from(value),
+                Err(_) => serde_json::Value::String(value.to_string()),
+            },
+            subxt::ext::scale_value::Primitive::I128(value) => match i64::try_from(*value) {
+                Ok(value) => serde_json::Value::from(value),
+                Err(_) => serde_json::Value::String(value.to_string()),
+            },
+            subxt::ext::scale_value::Primitive::U256(value)
+            | subxt::ext::scale_value::Primitive::I256(value) => {
+                serde_json::Value::String(format!("0x{}", hex::encode(value)))
+            }
+        },
+    }
+}
+
+fn composite_bytes(
+    types: &scale_info::PortableRegistry,
+    composite: &subxt::ext::scale_value::Composite<u32>,
+) -> Option<Vec<u8>> {
+    composite
+        .values()
+        .map(|item| match &item.value {
+            subxt::ext::scale_value::ValueDef::Primitive(
+                subxt::ext::scale_value::Primitive::U128(byte),
+            ) if is_byte(types, item.context) => Some(*byte as u8),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn composite_to_json(
+    types: &scale_info::PortableRegistry,
+    ss58_prefix: u16,
+    composite: &subxt::ext::scale_value::Composite<u32>,
+) -> serde_json::Value {
+    match composite {
+        subxt::ext::scale_value::Composite::Named(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(name, item)| (name.clone(), value_to_json(types, ss58_prefix, item)))
+                .collect(),
+        ),
+        subxt::ext::scale_value::Composite::Unnamed(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(|item| value_to_json(types, ss58_prefix, item))
+                .collect(),
+        ),
+    }
+}
+
+impl crate::WalletInstance {
+    /// Encode `pallet_name::call_name` from the live metadata of the runtime.
+    ///
+    /// `json_args` is an array of the call arguments in order, an object keyed by argument name or
+    /// `null` for calls without arguments.
+    pub(crate) fn dynamic_call(
+        &self,
+        pallet_name: &str,
+        call_name: &str,
+        json_args: &serde_json::Value,
+    ) -> crate::Result<subxt::tx::DynamicPayload> {
+        let metadata = self.client.metadata();
+        let pallet = find_pallet(&metadata, pallet_name)?;
+        let call = pallet
+            .call_variants()
+            .unwrap_or_default()
+            .iter()
+            .find(|call| same_name(&call.name, call_name))
+            .ok_or_else(|| {
+                crate::Error::MetadataNotFound(format!("Call {pallet_name}::{call_name}"))
+            })?;
+        let call_fields = json_to_fields(metadata.types(), &call.fields, json_args)?;
+
+        Ok(subxt::dynamic::tx(pallet.name(), &call.name, call_fields))
+    }
+
+    pub async fn submit_call(
+        &self,
+        sender_address: &str,
+        pallet_name: &str,
+        call_name: &str,
+        json_args: &serde_json::Value,
+    ) -> crate::Result<String> {
+        self.submit_call_with_options(
+            sender_address,
+            pallet_name,
+            call_name,
+            json_args,
+            &crate::TxOptions::default(),
+        )
+        .await
+    }
+
+    /// Submit any call of the runtime, including the ones the wallet does not wrap.
+    ///
+    /// Fails with the dispatch error when the call was included but did not succeed.
+    pub async fn submit_call_with_options(
+        &self,
+        sender_address: &str,
+        pallet_name: &str,
+        call_name: &str,
+        json_args: &serde_json::Value,
+        options: &crate::TxOptions,
+    ) -> crate::Result<String> {
+        let tx_payload = self.dynamic_call(pallet_name, call_name, json_args)?;
+        let progress = self
+            .submit_with_options(sender_address, &[], &tx_payload, options)
+            .await?;
+        let events = progress
+            .wait_for_in_block()
+            .await?
+            .wait_for_success()
+            .await?;
+
+        Ok(self.block_url(events.block_hash()))
+    }
+
+    /// Read any storage entry of the runtime, decoded to JSON through live metadata.
+    ///
+    /// `keys_json` is an array of the map keys in order, `null` for plain entries. Entries with a
+    /// default value return it when unset, the others return `null`.
+    pub async fn query_storage(
+        &self,
+        pallet_name: &str,
+        entry_name: &str,
+        keys_json: &serde_json::Value,
+        at: Option<crate::BlockId>,
+    ) -> crate::Result<serde_json::Value> {
+        let metadata = self.client.metadata();
+        let types = metadata.types();
+        let pallet = find_pallet(&metadata, pallet_name)?;
+        let entry = pallet
+            .storage()
+            .and_then(|storage| {
+                storage
+                    .entries()
+                    .iter()
+                    .find(|entry| same_name(entry.name(), entry_name))
+            })
+            .ok_or_else(|| {
+                crate::Error::MetadataNotFound(format!("Storage {pallet_name}::{entry_name}"))
+            })?;
+        let key_type_ids = match entry.entry_type() {
+            subxt::metadata::types::StorageEntryType::Plain(_) => Vec::new(),
+            subxt::metadata::types::StorageEntryType::Map {
+                hashers, key_ty, ..
+            } if hashers.len() == 1 => vec![*key_ty],
+            subxt::metadata::types::StorageEntryType::Map { key_ty, .. } => {
+                match &resolve_type(types, *key_ty)?.type_def {
+                    scale_info::TypeDef::Tuple(tuple) => {
+                        tuple.fields.iter().map(|field| field.id).collect()
+                    }
+                    _ => vec![*key_ty],
+                }
+            }
+        };
+        let keys_json = match keys_json {
+            serde_json::Value::Null => Vec::new(),
+            serde_json::Value::Array(keys_json) => keys_json.iter().collect(),
+            key_json => vec![key_json],
+        };
+
+        if keys_json.len() != key_type_ids.len() {
+            return Err(crate::Error::InvalidArgument(format!(
+                "{pallet_name}::{entry_name} takes {} keys but {} were given",
+                key_type_ids.len(),
+                keys_json.len()
+            )));
+        }
+
+        let keys = key_type_ids
+            .into_iter()
+            .zip(keys_json)
+            .map(|(key_type_id, key_json)| json_to_value(types, key_type_id, key_json))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let data_pointer = subxt::dynamic::storage(pallet.name(), entry.name(), keys);
+        let storage = match at {
+            Some(block) => self.client.storage().at(self.block_hash(block).await?),
+            None => self.client.storage().at_latest().await?,
+        };
+        let maybe_value = match entry.modifier() {
+            subxt::metadata::types::StorageEntryModifier::Default => {
+                Some(storage.fetch_or_default(&data_pointer).await?)
+            }
+            subxt::metadata::types::StorageEntryModifier::Optional => {
+                storage.fetch(&data_pointer).await?
+            }
+        };
+
+        match maybe_value {
+            Some(value) => Ok(value_to_json(
+                types,
+                self.network.ss58_prefix,
+                &value.to_value()?,
+            )),
+            None => Ok(serde_json::Value::Null),
+        }
+    }
+
+    /// Read any constant of the runtime, decoded to JSON through live metadata.
+    pub fn query_constant(
+        &self,
+        pallet_name: &str,
+        constant_name: &str,
+    ) -> crate::Result<serde_json::Value> {
+        let metadata = self.client.metadata();
+        let pallet = find_pallet(&metadata, pallet_name)?;
+        let constant = pallet
+            .constants()
+            .find(|constant| same_name(constant.name(), constant_name))
+            .ok_or_else(|| {
+                crate::Error::MetadataNotFound(format!("Constant {pallet_name}::{constant_name}"))
+            })?;
+        let constant_address = subxt::dynamic::constant(pallet.name(), constant.name());
+        let value = self.client.constants().at(&constant_address)?.to_value()?;
+
+        Ok(value_to_json(
+            metadata.types(),
+            self.network.ss58_prefix,
+            &value,
+        ))
+    }
+}
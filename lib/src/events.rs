@@ -0,0 +1,172 @@
+/// Runtime events the wallet reads, decoded the same way whatever the runtime mode.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq)]
+pub(crate) enum WalletEvent {
+    /// `Balances::Transfer` when `asset_id` is `None`, `Assets::Transferred` otherwise.
+    Transfer {
+        asset_id: Option<u32>,
+        from: subxt::utils::AccountId32,
+        to: subxt::utils::AccountId32,
+        amount: u128,
+    },
+    FeePaid {
+        who: subxt::utils::AccountId32,
+        actual_fee: u128,
+    },
+    /// `caller` is `None` for root.
+    ContractCalled {
+        caller: Option<subxt::utils::AccountId32>,
+        contract: subxt::utils::AccountId32,
+    },
+    ExtrinsicSuccess,
+    ExtrinsicFailed,
+}
+
+pub(crate) type EventDetails = subxt::events::EventDetails<subxt::PolkadotConfig>;
+
+/// Extrinsic the event was emitted by, `None` for block initialization and finalization.
+pub(crate) fn extrinsic_index(event: &EventDetails) -> Option<u32> {
+    match event.phase() {
+        subxt::events::Phase::ApplyExtrinsic(extrinsic_index) => Some(extrinsic_index),
+        _ => None,
+    }
+}
+
+pub(crate) fn decode_event(
+    event: &EventDetails,
+    runtime_mode: crate::RuntimeMode,
+) -> crate::Result<Option<WalletEvent>> {
+    if runtime_mode == crate::RuntimeMode::Static {
+        static_event(event)
+    } else {
+        dynamic_event(event)
+    }
+}
+
+fn static_event(event: &EventDetails) -> crate::Result<Option<WalletEvent>> {
+    if let Some(transfer) =
+        event.as_event::<crate::metadata::nagara::api::balances::events::Transfer>()?
+    {
+        return Ok(Some(WalletEvent::Transfer {
+            asset_id: None,
+            from: transfer.from,
+            to: transfer.to,
+            amount: transfer.amount,
+        }));
+    }
+
+    if let Some(transferred) =
+        event.as_event::<crate::metadata::nagara::api::assets::events::Transferred>()?
+    {
+        return Ok(Some(WalletEvent::Transfer {
+            asset_id: Some(transferred.asset_id),
+            from: transferred.from,
+            to: transferred.to,
+            amount: transferred.amount,
+        }));
+    }
+
+    if let Some(fee_paid) = event.as_event::<
+        crate::metadata::nagara::api::transaction_payment::events::TransactionFeePaid,
+    >()? {
+        return Ok(Some(WalletEvent::FeePaid {
+            who: fee_paid.who,
+            actual_fee: fee_paid.actual_fee,
+        }));
+    }
+
+    if let Some(called) =
+        event.as_event::<crate::metadata::nagara::api::contracts::events::Called>()?
+    {
+        let caller = match called.caller {
+            crate::metadata::nagara::api::runtime_types::pallet_contracts::Origin::Signed(
+                caller,
+            ) => Some(caller),
+            _ => None,
+        };
+
+        return Ok(Some(WalletEvent::ContractCalled {
+            caller,
+            contract: called.contract,
+        }));
+    }
+
+    if event
+        .as_event::<crate::metadata::nagara::api::system::events::ExtrinsicSuccess>()?
+        .is_some()
+    {
+        return Ok(Some(WalletEvent::ExtrinsicSuccess));
+    }
+
+    if event
+        .as_event::<crate::metadata::nagara::api::system::events::ExtrinsicFailed>()?
+        .is_some()
+    {
+        return Ok(Some(WalletEvent::ExtrinsicFailed));
+    }
+
+    Ok(None)
+}
+
+fn dynamic_event(event: &EventDetails) -> crate::Result<Option<WalletEvent>> {
+    let event_name = (event.pallet_name(), event.variant_name());
+
+    match event_name {
+        ("System", "ExtrinsicSuccess") => return Ok(Some(WalletEvent::ExtrinsicSuccess)),
+        ("System", "ExtrinsicFailed") => return Ok(Some(WalletEvent::ExtrinsicFailed)),
+        ("Balances", "Transfer")
+        | ("Assets", "Transferred")
+        | ("TransactionPayment", "TransactionFeePaid")
+        | ("Contracts", "Called") => {}
+        _ => return Ok(None),
+    }
+
+    let fields = event.field_values()?;
+    let field = |name: &str| subxt::dynamic::At::at(&fields, name);
+    let account_field = |name: &str| field(name).and_then(crate::compat::dynamic_account_id);
+    let u128_field = |name: &str| field(name).and_then(|value| value.as_u128());
+    let wallet_event = match event_name {
+        ("Balances", "Transfer") => {
+            match (
+                account_field("from"),
+                account_field("to"),
+                u128_field("amount"),
+            ) {
+                (Some(from), Some(to), Some(amount)) => Some(WalletEvent::Transfer {
+                    asset_id: None,
+                    from,
+                    to,
+                    amount,
+                }),
+                _ => None,
+            }
+        }
+        ("Assets", "Transferred") => match (
+            u128_field("asset_id"),
+            account_field("from"),
+            account_field("to"),
+            u128_field("amount"),
+        ) {
+            (Some(asset_id), Some(from), Some(to), Some(amount)) => Some(WalletEvent::Transfer {
+                asset_id: Some(asset_id as u32),
+                from,
+                to,
+                amount,
+            }),
+            _ => None,
+        },
+        ("TransactionPayment", "TransactionFeePaid") => {
+            match (account_field("who"), u128_field("actual_fee")) {
+                (Some(who), Some(actual_fee)) => Some(WalletEvent::FeePaid { who, actual_fee }),
+                _ => None,
+            }
+        }
+        _ => account_field("contract").map(|contract| WalletEvent::ContractCalled {
+            caller: field("caller")
+                .and_then(|caller| subxt::dynamic::At::at(caller, 0))
+                .and_then(crate::compat::dynamic_account_id),
+            contract,
+        }),
+    };
+
+    Ok(wallet_event)
+}
@@ -0,0 +1,282 @@
+pub type ExtrinsicHash = subxt::utils::H256;
+
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub enum Era {
+    Immortal,
+    Mortal { period: u64, phase: u64 },
+}
+
+impl Era {
+    fn decode(input: &mut &[u8]) -> crate::Result<Self> {
+        let first = <u8 as parity_scale_codec::Decode>::decode(input).map_err(decode_error)?;
+
+        if first == 0 {
+            return Ok(Self::Immortal);
+        }
+
+        let second = <u8 as parity_scale_codec::Decode>::decode(input).map_err(decode_error)?;
+        let encoded = first as u64 + ((second as u64) << 8);
+        let period = 2 << (encoded % (1 << 4));
+        let quantize_factor = (period >> 12).max(1);
+        let phase = (encoded >> 4) * quantize_factor;
+
+        Ok(Self::Mortal { period, phase })
+    }
+}
+
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub enum SignatureKind {
+    Sr25519,
+    Ed25519,
+    Ecdsa,
+}
+
+/// An extrinsic decoded from its bytes, see [`crate::WalletInstance::decode_extrinsic`].
+#[derive(Clone, core::fmt::Debug, PartialEq, serde::Serialize)]
+pub struct DecodedExtrinsic {
+    pub hash: ExtrinsicHash,
+    /// `None` for unsigned extrinsics.
+    pub signature: Option<DecodedSignature>,
+    pub call: CallInfo,
+}
+
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub struct DecodedSignature {
+    pub signer_address: String,
+    pub signature_kind: SignatureKind,
+    pub era: Era,
+    pub nonce: u64,
+    pub tip: u128,
+}
+
+/// A call with the calls it dispatches, like the ones of `Utility::batch` or `Sudo::sudo`.
+#[derive(Clone, core::fmt::Debug, PartialEq, serde::Serialize)]
+pub struct CallInfo {
+    pub pallet: String,
+    pub call: String,
+    /// Arguments as JSON, nested calls included.
+    pub args: serde_json::Value,
+    pub nested_calls: Vec<CallInfo>,
+}
+
+/// The signed part of an extrinsic, following the signed extensions of the nagara runtime.
+#[derive(Clone, core::fmt::Debug)]
+pub(crate) struct ExtrinsicSignature {
+    pub(crate) signer: subxt::utils::AccountId32,
+    pub(crate) signature_kind: SignatureKind,
+    pub(crate) era: Era,
+    pub(crate) nonce: u64,
+    pub(crate) tip: u128,
+}
+
+/// An extrinsic split into its signature and call bytes without the help of runtime metadata.
+#[derive(Clone, core::fmt::Debug)]
+pub(crate) struct ExtrinsicEnvelope {
+    pub(crate) hash: ExtrinsicHash,
+    pub(crate) signature: Option<ExtrinsicSignature>,
+    pub(crate) call_data: Vec<u8>,
+}
+
+impl ExtrinsicEnvelope {
+    const SUPPORTED_VERSION: u8 = 4;
+    const SIGNED_BIT: u8 = 0b1000_0000;
+
+    /// Decode a length prefixed extrinsic, as returned by the node.
+    pub(crate) fn decode(encoded: &[u8]) -> crate::Result<Self> {
+        let hash = <subxt::config::substrate::BlakeTwo256 as subxt::config::Hasher>::hash(encoded);
+        let mut input = encoded;
+        let _length =
+            <parity_scale_codec::Compact<u32> as parity_scale_codec::Decode>::decode(&mut input)
+                .map_err(decode_error)?;
+        let version =
+            <u8 as parity_scale_codec::Decode>::decode(&mut input).map_err(decode_error)?;
+
+        if version & !Self::SIGNED_BIT != Self::SUPPORTED_VERSION {
+            return Err(crate::Error::OtherError(format!(
+                "Unsupported extrinsic version {}",
+                version & !Self::SIGNED_BIT
+            )));
+        }
+
+        let signature = if version & Self::SIGNED_BIT != 0 {
+            let address_kind =
+                <u8 as parity_scale_codec::Decode>::decode(&mut input).map_err(decode_error)?;
+
+            if address_kind != 0 {
+                return Err(crate::Error::OtherError(
+                    "Only AccountId signer addresses are supported".to_string(),
+                ));
+            }
+
+            let signer = <[u8; 32] as parity_scale_codec::Decode>::decode(&mut input)
+                .map_err(decode_error)?;
+            let signature =
+                <subxt::utils::MultiSignature as parity_scale_codec::Decode>::decode(&mut input)
+                    .map_err(decode_error)?;
+            let signature_kind = match signature {
+                subxt::utils::MultiSignature::Sr25519(_) => SignatureKind::Sr25519,
+                subxt::utils::MultiSignature::Ed25519(_) => SignatureKind::Ed25519,
+                subxt::utils::MultiSignature::Ecdsa(_) => SignatureKind::Ecdsa,
+            };
+            let era = Era::decode(&mut input)?;
+            let nonce = <parity_scale_codec::Compact<u64> as parity_scale_codec::Decode>::decode(
+                &mut input,
+            )
+            .map_err(decode_error)?;
+            let tip = <parity_scale_codec::Compact<u128> as parity_scale_codec::Decode>::decode(
+                &mut input,
+            )
+            .map_err(decode_error)?;
+
+            Some(ExtrinsicSignature {
+                signer: subxt::utils::AccountId32(signer),
+                signature_kind,
+                era,
+                nonce: nonce.0,
+                tip: tip.0,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            hash,
+            signature,
+            call_data: input.to_vec(),
+        })
+    }
+}
+
+/// Decode a call with the calls nested in its arguments, `None` when the value is not a call.
+fn call_info(
+    types: &scale_info::PortableRegistry,
+    ss58_prefix: u16,
+    call_type_id: u32,
+    value: &subxt::dynamic::DecodedValue,
+) -> Option<CallInfo> {
+    let subxt::ext::scale_value::ValueDef::Variant(pallet) = &value.value else {
+        return None;
+    };
+    let subxt::ext::scale_value::ValueDef::Variant(call) = &pallet.values.values().next()?.value
+    else {
+        return None;
+    };
+    let mut nested_calls = Vec::new();
+
+    for arg in call.values.values() {
+        collect_calls(types, ss58_prefix, call_type_id, arg, &mut nested_calls);
+    }
+
+    Some(CallInfo {
+        pallet: pallet.name.clone(),
+        call: call.name.clone(),
+        args: crate::dynamic::composite_to_json(types, ss58_prefix, &call.values),
+        nested_calls,
+    })
+}
+
+fn collect_calls(
+    types: &scale_info::PortableRegistry,
+    ss58_prefix: u16,
+    call_type_id: u32,
+    value: &subxt::dynamic::DecodedValue,
+    calls: &mut Vec<CallInfo>,
+) {
+    if value.context == call_type_id {
+        calls.extend(call_info(types, ss58_prefix, call_type_id, value));
+
+        return;
+    }
+
+    let items = match &value.value {
+        subxt::ext::scale_value::ValueDef::Composite(composite) => composite,
+        subxt::ext::scale_value::ValueDef::Variant(variant) => &variant.values,
+        _ => return,
+    };
+
+    for item in items.values() {
+        collect_calls(types, ss58_prefix, call_type_id, item, calls);
+    }
+}
+
+/// Already encoded call data, used to sign the call of another extrinsic again.
+pub(crate) struct RawCall(pub(crate) Vec<u8>);
+
+impl subxt::tx::TxPayload for RawCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &subxt::Metadata,
+        out: &mut Vec<u8>,
+    ) -> core::result::Result<(), subxt::Error> {
+        out.extend_from_slice(&self.0);
+
+        Ok(())
+    }
+}
+
+fn decode_error(err: parity_scale_codec::Error) -> crate::Error {
+    crate::Error::OtherError(format!("Malformed extrinsic: {err}"))
+}
+
+impl crate::WalletInstance {
+    /// Decode a `0x` hex extrinsic, with or without its length prefix.
+    ///
+    /// The call is decoded through the metadata of the current runtime and must use all of the
+    /// remaining bytes.
+    pub fn decode_extrinsic(&self, extrinsic_hex: &str) -> crate::Result<DecodedExtrinsic> {
+        let mut encoded =
+            hex::decode(extrinsic_hex.trim().trim_start_matches("0x")).map_err(|err| {
+                crate::Error::InvalidArgument(format!("Extrinsic is not valid hex: {err}"))
+            })?;
+        let mut input = encoded.as_slice();
+        let length_prefixed =
+            <parity_scale_codec::Compact<u32> as parity_scale_codec::Decode>::decode(&mut input)
+                .map_or(false, |length| length.0 as usize == input.len());
+
+        if !length_prefixed {
+            encoded = <Vec<u8> as parity_scale_codec::Encode>::encode(&encoded);
+        }
+
+        let envelope = ExtrinsicEnvelope::decode(&encoded)?;
+        let call = self.decode_call(&envelope.call_data)?;
+        let signature = envelope.signature.map(|signature| DecodedSignature {
+            signer_address: self.network.encode_address(&signature.signer),
+            signature_kind: signature.signature_kind,
+            era: signature.era,
+            nonce: signature.nonce,
+            tip: signature.tip,
+        });
+
+        Ok(DecodedExtrinsic {
+            hash: envelope.hash,
+            signature,
+            call,
+        })
+    }
+
+    /// Decode call data through the metadata of the current runtime, it must use all of the bytes.
+    pub(crate) fn decode_call(&self, call_data: &[u8]) -> crate::Result<CallInfo> {
+        let metadata = self.client.metadata();
+        let types = metadata.types();
+        let call_type_id = metadata.extrinsic().call_ty();
+        let mut call_data = call_data;
+        let call_value =
+            subxt::ext::scale_value::scale::decode_as_type(&mut call_data, call_type_id, types)
+                .map_err(|err| {
+                    crate::Error::OtherError(format!(
+                        "Call doesn't match the runtime metadata: {err}"
+                    ))
+                })?;
+
+        if !call_data.is_empty() {
+            return Err(crate::Error::OtherError(format!(
+                "Call doesn't match the runtime metadata: {} bytes left over",
+                call_data.len()
+            )));
+        }
+
+        call_info(types, self.network.ss58_prefix, call_type_id, &call_value).ok_or_else(|| {
+            crate::Error::OtherError("Call doesn't match the runtime metadata".to_string())
+        })
+    }
+}
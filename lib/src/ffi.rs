@@ -0,0 +1,294 @@
+//! C API of the wallet.
+//!
+//! Functions failing return `NULL`, the code and the message of the error are kept for the calling
+//! thread by [`nagara_wallet_last_error_code`] and [`nagara_wallet_last_error_message`].
+//!
+//! Strings returned by the API must be freed with [`nagara_wallet_free_string`]. A panic inside the
+//! wallet is caught and reported as an error instead of unwinding into the caller.
+
+std::thread_local! {
+    static LAST_ERROR: core::cell::RefCell<Option<(i32, String)>> =
+        core::cell::RefCell::new(None);
+}
+
+/// A wallet with the async runtime driving it, see [`nagara_wallet_create`].
+#[safer_ffi::derive_ReprC]
+#[repr(opaque)]
+pub struct NagaraWallet {
+    instance: crate::WalletInstance,
+    runtime: tokio::runtime::Runtime,
+}
+
+fn set_last_error(err: crate::Error) {
+    let message = err.to_string();
+    let code = i32::from(err);
+
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some((code, message)));
+}
+
+fn ffi_result<T>(result: crate::Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => {
+            LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+
+            Some(value)
+        }
+        Err(err) => {
+            set_last_error(err);
+
+            None
+        }
+    }
+}
+
+/// Run `call` for an exported function, a panic becomes an `OtherError`.
+fn ffi_call<T, F: FnOnce() -> crate::Result<T>>(call: F) -> Option<T> {
+    let result =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(call)).unwrap_or_else(|panic| {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+
+            Err(crate::Error::OtherError(format!(
+                "Wallet panicked: {message}"
+            )))
+        });
+
+    ffi_result(result)
+}
+
+fn ffi_string(string: String) -> crate::Result<safer_ffi::prelude::char_p::Box> {
+    safer_ffi::prelude::char_p::Box::try_from(string)
+        .map_err(|err| crate::Error::OtherError(err.to_string()))
+}
+
+fn parse_amount(amount: safer_ffi::prelude::char_p::Ref<'_>) -> crate::Result<u128> {
+    amount
+        .to_str()
+        .parse()
+        .map_err(|err| crate::Error::InvalidArgument(format!("Invalid amount: {err}")))
+}
+
+/// Connect to `rpc_url`, or to nagara mainnet when it is `NULL`.
+///
+/// Returns `NULL` on failure, the wallet must be freed with `nagara_wallet_free`.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_create(
+    rpc_url: Option<safer_ffi::prelude::char_p::Ref<'_>>,
+) -> Option<safer_ffi::prelude::repr_c::Box<NagaraWallet>> {
+    nagara_wallet_create_with_network(None, rpc_url, None)
+}
+
+/// Connect to a network profile (`mainnet`, `testnet` or `local`, mainnet when `NULL`), through
+/// `rpc_url` instead of its own endpoints when not `NULL`. The node must have `genesis_hash`, a
+/// `0x` prefixed hash, when it is not `NULL`.
+///
+/// Returns `NULL` on failure, the wallet must be freed with `nagara_wallet_free`.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_create_with_network(
+    network: Option<safer_ffi::prelude::char_p::Ref<'_>>,
+    rpc_url: Option<safer_ffi::prelude::char_p::Ref<'_>>,
+    genesis_hash: Option<safer_ffi::prelude::char_p::Ref<'_>>,
+) -> Option<safer_ffi::prelude::repr_c::Box<NagaraWallet>> {
+    ffi_call(|| {
+        let network_name = network.map_or("mainnet", |network| network.to_str());
+        let mut network = crate::NetworkConfig::from_name(network_name).ok_or_else(|| {
+            crate::Error::InvalidArgument(format!("Unknown network {network_name}"))
+        })?;
+
+        if let Some(rpc_url) = rpc_url {
+            network = network.with_rpc_urls(&[rpc_url.to_str()]);
+        }
+
+        if let Some(genesis_hash) = genesis_hash {
+            let genesis_hash = <crate::BlockHash as core::str::FromStr>::from_str(
+                genesis_hash.to_str(),
+            )
+            .map_err(|err| crate::Error::InvalidArgument(format!("Invalid genesis hash: {err}")))?;
+            network = network.with_genesis_hash(genesis_hash);
+        }
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+        let instance = runtime.block_on(crate::WalletInstance::create_with_network(network))?;
+
+        Ok(safer_ffi::prelude::repr_c::Box::new(NagaraWallet {
+            instance,
+            runtime,
+        }))
+    })
+}
+
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_free(wallet: Option<safer_ffi::prelude::repr_c::Box<NagaraWallet>>) {
+    drop(wallet);
+}
+
+/// Add the account of a private key hex or mnemonic, sr25519 unless `use_schnorrkel` is false.
+///
+/// Returns its SS58 address, or `NULL` on failure.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_add_account(
+    wallet: &mut NagaraWallet,
+    secret: safer_ffi::prelude::char_p::Ref<'_>,
+    use_schnorrkel: bool,
+) -> Option<safer_ffi::prelude::char_p::Box> {
+    ffi_call(|| {
+        wallet
+            .instance
+            .add_account(secret.to_str(), use_schnorrkel)
+            .and_then(ffi_string)
+    })
+}
+
+/// Balance of the account at the latest block, as a decimal string in the smallest unit of the
+/// native token.
+///
+/// Returns `NULL` on failure.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_check_balance(
+    wallet: &NagaraWallet,
+    account_address: safer_ffi::prelude::char_p::Ref<'_>,
+) -> Option<safer_ffi::prelude::char_p::Box> {
+    ffi_call(|| {
+        let account_address = account_address.to_str();
+        crate::compat::parse_account(account_address)?;
+        let balance = wallet
+            .runtime
+            .block_on(wallet.instance.check_balance(account_address))?;
+
+        ffi_string(balance.to_string())
+    })
+}
+
+/// Transfer `amount`, a decimal string in the smallest unit of the native token, from an account
+/// added to the wallet and wait for its inclusion.
+///
+/// Returns the explorer URL of the including block, or `NULL` on failure.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_transfer(
+    wallet: &NagaraWallet,
+    sender_address: safer_ffi::prelude::char_p::Ref<'_>,
+    recipient_address: safer_ffi::prelude::char_p::Ref<'_>,
+    amount: safer_ffi::prelude::char_p::Ref<'_>,
+) -> Option<safer_ffi::prelude::char_p::Box> {
+    ffi_call(|| {
+        let amount = parse_amount(amount)?;
+        let recipient_address = recipient_address.to_str();
+        crate::compat::parse_account(recipient_address)?;
+        let block_url = wallet.runtime.block_on(wallet.instance.transfer(
+            sender_address.to_str(),
+            recipient_address,
+            amount,
+        ))?;
+
+        ffi_string(block_url)
+    })
+}
+
+/// Code of the last error of the calling thread, `0` when its last call succeeded.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_last_error_code() -> i32 {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ref().map_or(0, |(code, _)| *code))
+}
+
+/// Message of the last error of the calling thread, `NULL` when its last call succeeded.
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_last_error_message() -> Option<safer_ffi::prelude::char_p::Box> {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .and_then(|(_, message)| ffi_string(message.clone()).ok())
+    })
+}
+
+#[safer_ffi::ffi_export]
+pub fn nagara_wallet_free_string(string: Option<safer_ffi::prelude::char_p::Box>) {
+    drop(string);
+}
+
+/// Write the C header of the API to `path`.
+#[safer_ffi::cfg_headers]
+pub fn generate_headers<P: core::convert::AsRef<std::path::Path>>(path: P) -> std::io::Result<()> {
+    safer_ffi::headers::builder()
+        .with_guard("NAGARA_SIMPLE_WALLET_H")
+        .to_file(path)?
+        .generate()
+}
+
+#[cfg(test)]
+mod tests {
+    fn dev_node_url() -> safer_ffi::prelude::char_p::Box {
+        let rpc_url = std::env::var("NAGARA_WALLET_TEST_RPC_URL")
+            .unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string());
+
+        safer_ffi::prelude::char_p::Box::try_from(rpc_url).unwrap()
+    }
+
+    #[test]
+    fn create_refuses_unknown_network() {
+        let wallet =
+            super::nagara_wallet_create_with_network(Some(safer_ffi::c!("nowhere")), None, None);
+
+        assert!(wallet.is_none());
+        assert_eq!(super::nagara_wallet_last_error_code(), -15);
+    }
+
+    #[test]
+    fn create_refuses_malformed_genesis_hash() {
+        let wallet = super::nagara_wallet_create_with_network(
+            Some(safer_ffi::c!("local")),
+            None,
+            Some(safer_ffi::c!("0x1234")),
+        );
+
+        assert!(wallet.is_none());
+        assert_eq!(super::nagara_wallet_last_error_code(), -15);
+        assert!(super::nagara_wallet_last_error_message()
+            .unwrap()
+            .to_str()
+            .contains("genesis hash"));
+    }
+
+    #[test]
+    fn create_reports_unreachable_node() {
+        let wallet = super::nagara_wallet_create_with_network(
+            Some(safer_ffi::c!("local")),
+            Some(safer_ffi::c!("ws://127.0.0.1:1")),
+            None,
+        );
+
+        assert!(wallet.is_none());
+        assert_eq!(super::nagara_wallet_last_error_code(), -11);
+    }
+
+    #[test]
+    #[ignore = "needs a dev node, at NAGARA_WALLET_TEST_RPC_URL or ws://127.0.0.1:9944"]
+    fn create_connects_to_dev_node() {
+        let rpc_url = dev_node_url();
+        let wallet = super::nagara_wallet_create_with_network(
+            Some(safer_ffi::c!("local")),
+            Some(rpc_url.as_ref()),
+            None,
+        )
+        .expect("Wallet connects to the dev node");
+        assert_eq!(super::nagara_wallet_last_error_code(), 0);
+
+        let balance = super::nagara_wallet_check_balance(
+            &wallet,
+            safer_ffi::c!("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"),
+        );
+        assert!(balance.is_some());
+
+        let balance = super::nagara_wallet_check_balance(&wallet, safer_ffi::c!("not-an-address"));
+        assert!(balance.is_none());
+        assert_eq!(super::nagara_wallet_last_error_code(), -15);
+
+        super::nagara_wallet_free(Some(wallet));
+    }
+}
@@ -0,0 +1,168 @@
+pub type BlockHeader = <subxt::PolkadotConfig as subxt::Config>::Header;
+
+/// Best and finalized heads followed by the head tracker of the wallet.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub struct ChainHeads {
+    pub best_hash: crate::BlockHash,
+    pub best: BlockHeader,
+    pub finalized_hash: crate::BlockHash,
+    pub finalized: BlockHeader,
+}
+
+impl ChainHeads {
+    pub fn best_number(&self) -> u32 {
+        self.best.number
+    }
+
+    pub fn finalized_number(&self) -> u32 {
+        self.finalized.number
+    }
+
+    /// Best blocks not finalized yet.
+    pub fn finality_lag(&self) -> u32 {
+        self.best.number.saturating_sub(self.finalized.number)
+    }
+
+    fn set_best(&mut self, header: BlockHeader) {
+        self.best_hash = <BlockHeader as subxt::config::Header>::hash(&header);
+        self.best = header;
+    }
+
+    fn set_finalized(&mut self, header: BlockHeader) {
+        self.finalized_hash = <BlockHeader as subxt::config::Header>::hash(&header);
+        self.finalized = header;
+
+        if self.best.number < self.finalized.number {
+            self.best_hash = self.finalized_hash;
+            self.best = self.finalized.clone();
+        }
+    }
+}
+
+const INITIAL_BACKOFF: core::time::Duration = core::time::Duration::from_secs(1);
+const MAX_BACKOFF: core::time::Duration = core::time::Duration::from_secs(30);
+
+enum HeadUpdate {
+    Best(BlockHeader),
+    Finalized(BlockHeader),
+}
+
+pub(crate) async fn fetch_chain_heads(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+) -> crate::Result<ChainHeads> {
+    let best_hash = client
+        .rpc()
+        .block_hash(None)
+        .await?
+        .ok_or(crate::Error::BlockNotFound)?;
+    let best = client
+        .rpc()
+        .header(Some(best_hash))
+        .await?
+        .ok_or(crate::Error::BlockNotFound)?;
+    let finalized_hash = client.rpc().finalized_head().await?;
+    let finalized = client
+        .rpc()
+        .header(Some(finalized_hash))
+        .await?
+        .ok_or(crate::Error::BlockNotFound)?;
+
+    Ok(ChainHeads {
+        best_hash,
+        best,
+        finalized_hash,
+        finalized,
+    })
+}
+
+/// Follow the best and finalized header subscriptions into `chain_heads`, subscribing again when
+/// they end, e.g. after a failover to another endpoint.
+///
+/// `stale` is set while there is no subscription, subscribing is retried with an exponential
+/// backoff.
+pub(crate) async fn track_chain_heads(
+    client: subxt::OnlineClient<subxt::PolkadotConfig>,
+    chain_heads: std::sync::Arc<std::sync::RwLock<ChainHeads>>,
+    listeners: std::sync::Arc<crate::task::Listeners<ChainHeads>>,
+    stale: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) {
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let subscriptions = futures::future::try_join(
+            client.rpc().subscribe_best_block_headers(),
+            client.rpc().subscribe_finalized_block_headers(),
+        )
+        .await;
+        let (best_headers, finalized_headers) = match subscriptions {
+            Ok(subscriptions) => subscriptions,
+            Err(err) => {
+                stale.store(true, std::sync::atomic::Ordering::Relaxed);
+                nagara_logging::info!("Heads can't be followed, retrying in {backoff:?}: {err}");
+                futures_timer::Delay::new(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+
+                continue;
+            }
+        };
+        let mut head_updates = futures::stream::select(
+            futures::StreamExt::map(best_headers, |header| header.map(HeadUpdate::Best)),
+            futures::StreamExt::map(finalized_headers, |header| {
+                header.map(HeadUpdate::Finalized)
+            }),
+        );
+
+        // Heads produced while there was no subscription are not notified again.
+        if let Ok(latest_heads) = fetch_chain_heads(&client).await {
+            *chain_heads.write().unwrap() = latest_heads.clone();
+            listeners.emit(latest_heads);
+        }
+
+        stale.store(false, std::sync::atomic::Ordering::Relaxed);
+        backoff = INITIAL_BACKOFF;
+
+        while let Some(head_update) = futures::StreamExt::next(&mut head_updates).await {
+            let head_update = match head_update {
+                Ok(head_update) => head_update,
+                Err(err) => {
+                    nagara_logging::info!("Head notification failed: {err}");
+
+                    continue;
+                }
+            };
+            let updated_heads = {
+                let mut chain_heads = chain_heads.write().unwrap();
+
+                match head_update {
+                    HeadUpdate::Best(header) => chain_heads.set_best(header),
+                    HeadUpdate::Finalized(header) => chain_heads.set_finalized(header),
+                }
+
+                chain_heads.clone()
+            };
+            listeners.emit(updated_heads);
+        }
+
+        stale.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+impl crate::WalletInstance {
+    /// Latest best and finalized heads, kept up to date in the background without a request to
+    /// the node.
+    pub fn chain_heads(&self) -> ChainHeads {
+        self.chain_heads.read().unwrap().clone()
+    }
+
+    /// Whether the heads are not followed anymore, e.g. while the node is unreachable, so that
+    /// [`Self::chain_heads`] gives the last known ones.
+    pub fn chain_heads_stale(&self) -> bool {
+        self.chain_heads_stale
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Receive the heads every time the best or the finalized head moved.
+    pub fn chain_head_updates(&self) -> futures::channel::mpsc::UnboundedReceiver<ChainHeads> {
+        self.chain_head_updates.subscribe()
+    }
+}
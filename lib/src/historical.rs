@@ -0,0 +1,142 @@
+/// Block to read the state at, by hash or by number.
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq)]
+pub enum BlockId {
+    Hash(crate::BlockHash),
+    Number(u32),
+}
+
+impl From<crate::BlockHash> for BlockId {
+    fn from(value: crate::BlockHash) -> Self {
+        Self::Hash(value)
+    }
+}
+
+impl From<u32> for BlockId {
+    fn from(value: u32) -> Self {
+        Self::Number(value)
+    }
+}
+
+pub(crate) async fn fetch_timestamp(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: crate::RuntimeMode,
+    block_hash: crate::BlockHash,
+) -> crate::Result<u64> {
+    let storage = client.storage().at(block_hash);
+
+    if runtime_mode == crate::RuntimeMode::Dynamic {
+        let data_pointer = subxt::dynamic::storage_root("Timestamp", "Now");
+        let timestamp = storage.fetch_or_default(&data_pointer).await?.to_value()?;
+
+        return Ok(timestamp.as_u128().unwrap_or_default() as u64);
+    }
+
+    let data_pointer = crate::metadata::nagara::api::storage().timestamp().now();
+
+    Ok(storage.fetch_or_default(&data_pointer).await?)
+}
+
+impl crate::WalletInstance {
+    pub async fn block_hash(&self, block: BlockId) -> crate::Result<crate::BlockHash> {
+        match block {
+            BlockId::Hash(block_hash) => Ok(block_hash),
+            BlockId::Number(block_number) => self
+                .client
+                .rpc()
+                .block_hash(Some(block_number.into()))
+                .await?
+                .ok_or(crate::Error::BlockNotFound),
+        }
+    }
+
+    /// Balances of the account at the end of `block`, older blocks need an archive node.
+    pub async fn balance_info_at(
+        &self,
+        account_address: &str,
+        block: BlockId,
+    ) -> crate::Result<crate::BalanceInfo> {
+        let account = crate::compat::parse_account(account_address)?;
+        let (block_number, block_hash) = match block {
+            BlockId::Hash(block_hash) => {
+                let header = self
+                    .client
+                    .rpc()
+                    .header(Some(block_hash))
+                    .await?
+                    .ok_or(crate::Error::BlockNotFound)?;
+
+                (header.number, block_hash)
+            }
+            BlockId::Number(block_number) => (block_number, self.block_hash(block).await?),
+        };
+
+        crate::balance::fetch_balance_info(
+            &self.client,
+            self.compatible_runtime_mode()?,
+            &account,
+            block_number,
+            block_hash,
+        )
+        .await
+    }
+
+    pub async fn check_balance_at(
+        &self,
+        account_address: &str,
+        block: BlockId,
+    ) -> crate::Result<u128> {
+        Ok(self.balance_info_at(account_address, block).await?.total())
+    }
+
+    /// Balance of the account at the wall-clock instant, see [`Self::block_at_timestamp`].
+    pub async fn check_balance_at_timestamp(
+        &self,
+        account_address: &str,
+        timestamp_millis: u64,
+    ) -> crate::Result<u128> {
+        let (block_number, _) = self.block_at_timestamp(timestamp_millis).await?;
+
+        self.check_balance_at(account_address, BlockId::Number(block_number))
+            .await
+    }
+
+    /// Last finalized block produced at or before the unix timestamp in milliseconds.
+    ///
+    /// Binary searches `timestamp.now` over the finalized chain, an instant past the latest
+    /// finalized block gives that block.
+    pub async fn block_at_timestamp(
+        &self,
+        timestamp_millis: u64,
+    ) -> crate::Result<(u32, crate::BlockHash)> {
+        let crate::ChainHeads {
+            finalized_hash,
+            finalized: finalized_header,
+            ..
+        } = self.chain_heads();
+
+        if self.timestamp_at(finalized_hash).await? <= timestamp_millis {
+            return Ok((finalized_header.number, finalized_hash));
+        }
+
+        let mut low = 0;
+        let mut high = finalized_header.number;
+
+        while low < high {
+            let middle = low + (high - low + 1) / 2;
+            let middle_hash = self.block_hash(BlockId::Number(middle)).await?;
+
+            if self.timestamp_at(middle_hash).await? <= timestamp_millis {
+                low = middle;
+            } else {
+                high = middle - 1;
+            }
+        }
+
+        Ok((low, self.block_hash(BlockId::Number(low)).await?))
+    }
+
+    /// `timestamp.now` of the block in milliseconds, zero for the genesis block.
+    pub async fn timestamp_at(&self, block_hash: crate::BlockHash) -> crate::Result<u64> {
+        fetch_timestamp(&self.client, self.compatible_runtime_mode()?, block_hash).await
+    }
+}
@@ -0,0 +1,365 @@
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum LedgerKind {
+    Transfer,
+    AssetTransfer {
+        asset_id: u32,
+    },
+    ContractCall,
+    /// Extrinsic of the account moving nothing but its fee, like a remark or a failed call.
+    Fee,
+}
+
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum LedgerStatus {
+    Success,
+    Failed,
+}
+
+/// Movement involving the account, one per transfer, contract call or fee-only extrinsic.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LedgerEntry {
+    pub block_number: u32,
+    pub block_hash: crate::BlockHash,
+    /// Unix timestamp of the block in milliseconds.
+    pub timestamp: u64,
+    /// `None` for movements not made by an extrinsic, like scheduled ones.
+    pub extrinsic_index: Option<u32>,
+    /// Event the movement was read from, `None` for fee-only entries.
+    #[serde(default)]
+    pub event_index: Option<u32>,
+    /// `Pallet::call` of the extrinsic.
+    pub call: Option<String>,
+    pub kind: LedgerKind,
+    pub incoming: bool,
+    /// Other side of the movement, the contract for contract calls.
+    pub counterparty: Option<String>,
+    pub amount: u128,
+    /// Fee the account paid for the extrinsic, carried by its first entry only.
+    pub fee: u128,
+    pub status: LedgerStatus,
+}
+
+/// History of an account kept in a local index file, extended by
+/// [`crate::WalletInstance::sync_ledger`].
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AccountLedger {
+    pub address: String,
+    pub last_scanned_block: Option<u32>,
+    pub entries: Vec<LedgerEntry>,
+}
+
+impl AccountLedger {
+    /// Blocks scanned by [`crate::WalletInstance::sync_ledger_file`] between two saves.
+    pub const CHECKPOINT_BLOCKS: u32 = 1000;
+
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            last_scanned_block: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Read the index file, a missing file gives an empty ledger.
+    pub fn load<P: core::convert::AsRef<std::path::Path>>(
+        address: &str,
+        index_path: P,
+    ) -> crate::Result<Self> {
+        if !index_path.as_ref().exists() {
+            return Ok(Self::new(address));
+        }
+
+        let index =
+            std::fs::read(index_path).map_err(|err| crate::Error::OtherError(err.to_string()))?;
+        let ledger = serde_json::from_slice::<Self>(&index)
+            .map_err(|err| crate::Error::OtherError(format!("Corrupted ledger index: {err}")))?;
+
+        if ledger.address != address {
+            return Err(crate::Error::InvalidArgument(format!(
+                "Ledger index belongs to {}",
+                ledger.address
+            )));
+        }
+
+        Ok(ledger)
+    }
+
+    pub fn save<P: core::convert::AsRef<std::path::Path>>(
+        &self,
+        index_path: P,
+    ) -> crate::Result<()> {
+        let index_path = index_path.as_ref();
+        let index_path_tmp = index_path.with_extension("tmp");
+        let index =
+            serde_json::to_vec(self).map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        std::fs::write(&index_path_tmp, index)
+            .and_then(|_| std::fs::rename(&index_path_tmp, index_path))
+            .map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+}
+
+/// Movements of the block involving any of the accounts, each one paired with its account.
+pub(crate) async fn block_ledgers(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: crate::RuntimeMode,
+    ss58_prefix: u16,
+    accounts: &[subxt::utils::AccountId32],
+    block_number: u32,
+    block_hash: crate::BlockHash,
+) -> crate::Result<Vec<(subxt::utils::AccountId32, LedgerEntry)>> {
+    let events = client.events().at(block_hash).await?;
+    let mut entries = Vec::<(usize, LedgerEntry)>::new();
+    let mut fees = std::collections::BTreeMap::<(Option<u32>, usize), u128>::new();
+    let mut failed_extrinsics = std::collections::HashSet::<u32>::new();
+    let new_entry =
+        |extrinsic_index, event_index, kind, incoming, counterparty: Option<String>, amount| {
+            LedgerEntry {
+                block_number,
+                block_hash,
+                timestamp: 0,
+                extrinsic_index,
+                event_index,
+                call: None,
+                kind,
+                incoming,
+                counterparty,
+                amount,
+                fee: 0,
+                status: LedgerStatus::Success,
+            }
+        };
+
+    for event in events.iter() {
+        let event = event?;
+        let extrinsic_index = crate::events::extrinsic_index(&event);
+        let event_index = Some(event.index());
+        let wallet_event = match crate::events::decode_event(&event, runtime_mode)? {
+            Some(wallet_event) => wallet_event,
+            None => continue,
+        };
+
+        if wallet_event == crate::events::WalletEvent::ExtrinsicFailed {
+            failed_extrinsics.extend(extrinsic_index);
+        }
+
+        for (account_position, account) in accounts.iter().enumerate() {
+            match &wallet_event {
+                crate::events::WalletEvent::Transfer {
+                    asset_id,
+                    from,
+                    to,
+                    amount,
+                } if from == account || to == account => {
+                    let incoming = from != account;
+                    let counterparty = if incoming { from } else { to };
+                    let kind = match asset_id {
+                        Some(asset_id) => LedgerKind::AssetTransfer {
+                            asset_id: *asset_id,
+                        },
+                        None => LedgerKind::Transfer,
+                    };
+                    entries.push((
+                        account_position,
+                        new_entry(
+                            extrinsic_index,
+                            event_index,
+                            kind,
+                            incoming,
+                            Some(crate::network::encode_ss58(ss58_prefix, counterparty)),
+                            *amount,
+                        ),
+                    ));
+                }
+                crate::events::WalletEvent::ContractCalled { caller, contract }
+                    if caller.as_ref() == Some(account) || contract == account =>
+                {
+                    let incoming = contract == account;
+                    let counterparty = if incoming {
+                        caller.as_ref()
+                    } else {
+                        Some(contract)
+                    };
+                    entries.push((
+                        account_position,
+                        new_entry(
+                            extrinsic_index,
+                            event_index,
+                            LedgerKind::ContractCall,
+                            incoming,
+                            counterparty.map(|counterparty| {
+                                crate::network::encode_ss58(ss58_prefix, counterparty)
+                            }),
+                            0,
+                        ),
+                    ));
+                }
+                crate::events::WalletEvent::FeePaid { who, actual_fee } if who == account => {
+                    *fees.entry((extrinsic_index, account_position)).or_default() += actual_fee;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for ((extrinsic_index, account_position), fee) in fees {
+        match entries.iter_mut().find(|(entry_account, entry)| {
+            *entry_account == account_position && entry.extrinsic_index == extrinsic_index
+        }) {
+            Some((_, entry)) => entry.fee = fee,
+            None => {
+                let mut entry = new_entry(extrinsic_index, None, LedgerKind::Fee, false, None, 0);
+                entry.fee = fee;
+                entries.push((account_position, entry));
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    entries.sort_by_key(|(_, entry)| entry.extrinsic_index);
+
+    let timestamp = crate::historical::fetch_timestamp(client, runtime_mode, block_hash).await?;
+    let extrinsics = client.blocks().at(block_hash).await?.extrinsics().await?;
+    let calls = extrinsics
+        .iter()
+        .map(|extrinsic| -> crate::Result<String> {
+            let extrinsic = extrinsic?;
+
+            Ok(format!(
+                "{}::{}",
+                extrinsic.pallet_name()?,
+                extrinsic.variant_name()?
+            ))
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    Ok(entries
+        .into_iter()
+        .map(|(account_position, mut entry)| {
+            entry.timestamp = timestamp;
+
+            if let Some(extrinsic_index) = entry.extrinsic_index {
+                entry.call = calls.get(extrinsic_index as usize).cloned();
+
+                if failed_extrinsics.contains(&extrinsic_index) {
+                    entry.status = LedgerStatus::Failed;
+                }
+            }
+
+            (accounts[account_position].clone(), entry)
+        })
+        .collect())
+}
+
+impl crate::WalletInstance {
+    /// Movements involving the account from `from_block` to `to_block`, both included.
+    ///
+    /// Answered by the history index when it covers the range, otherwise every block of the range
+    /// is read, older ones need an archive node.
+    pub async fn account_history(
+        &self,
+        account_address: &str,
+        from_block: u32,
+        to_block: u32,
+    ) -> crate::Result<Vec<LedgerEntry>> {
+        #[cfg(feature = "index-db")]
+        if let Some(history_index) = &self.history_index {
+            if let Some(entries) =
+                history_index.account_history(account_address, from_block, to_block)?
+            {
+                return Ok(entries);
+            }
+        }
+
+        let account = <subxt::utils::AccountId32 as core::str::FromStr>::from_str(account_address)
+            .map_err(|_| {
+                crate::Error::InvalidArgument(format!("{account_address} is not an SS58 address"))
+            })?;
+        let runtime_mode = self.compatible_runtime_mode()?;
+        let mut entries = Vec::new();
+
+        for block_number in from_block..=to_block {
+            let block_hash = self
+                .block_hash(crate::BlockId::Number(block_number))
+                .await?;
+            let block_entries = block_ledgers(
+                &self.client,
+                runtime_mode,
+                self.network.ss58_prefix,
+                core::slice::from_ref(&account),
+                block_number,
+                block_hash,
+            )
+            .await?;
+            entries.extend(block_entries.into_iter().map(|(_, entry)| entry));
+        }
+
+        Ok(entries)
+    }
+
+    /// Scan the finalized blocks after the last scanned one into the ledger.
+    ///
+    /// A ledger never scanned before starts from `start_block`. Returns the number of new entries.
+    pub async fn sync_ledger(
+        &self,
+        ledger: &mut AccountLedger,
+        start_block: u32,
+    ) -> crate::Result<usize> {
+        self.sync_ledger_with(ledger, start_block, |_| Ok(())).await
+    }
+
+    /// Load the ledger of the account from the index file and sync it, saving it every
+    /// [`AccountLedger::CHECKPOINT_BLOCKS`] blocks so an interrupted sync resumes from the last
+    /// save. Returns the ledger and the number of new entries.
+    pub async fn sync_ledger_file<P: core::convert::AsRef<std::path::Path>>(
+        &self,
+        account_address: &str,
+        index_path: P,
+        start_block: u32,
+    ) -> crate::Result<(AccountLedger, usize)> {
+        let index_path = index_path.as_ref();
+        let mut ledger = AccountLedger::load(account_address, index_path)?;
+        let new_entries = self
+            .sync_ledger_with(&mut ledger, start_block, |ledger| ledger.save(index_path))
+            .await?;
+
+        Ok((ledger, new_entries))
+    }
+
+    async fn sync_ledger_with<F: FnMut(&AccountLedger) -> crate::Result<()>>(
+        &self,
+        ledger: &mut AccountLedger,
+        start_block: u32,
+        mut checkpoint: F,
+    ) -> crate::Result<usize> {
+        let finalized_number = self.chain_heads().finalized_number();
+        let mut from_block = ledger
+            .last_scanned_block
+            .map_or(start_block, |last_scanned_block| last_scanned_block + 1);
+        let mut new_entries = 0;
+
+        while from_block <= finalized_number {
+            let to_block = from_block
+                .saturating_add(AccountLedger::CHECKPOINT_BLOCKS - 1)
+                .min(finalized_number);
+            let entries = self
+                .account_history(&ledger.address, from_block, to_block)
+                .await?;
+            new_entries += entries.len();
+            ledger.entries.extend(entries);
+            ledger.last_scanned_block = Some(to_block);
+            checkpoint(ledger)?;
+
+            if to_block == finalized_number {
+                break;
+            }
+
+            from_block = to_block + 1;
+        }
+
+        Ok(new_entries)
+    }
+}
@@ -0,0 +1,500 @@
+const ACCOUNT_PREFIX: u8 = b'a';
+const BLOCK_PREFIX: u8 = b'b';
+const ENTRY_PREFIX: u8 = b'e';
+const BLOCK_ENTRY_PREFIX: u8 = b'n';
+const FIRST_BLOCK_KEY: &[u8] = b"mfirst_block";
+const FINALIZED_BLOCK_KEY: &[u8] = b"mfinalized_block";
+
+/// Ledger entries of the watched accounts kept in an embedded database, filled block after block
+/// by [`crate::WalletInstance::start_history_index`].
+///
+/// Blocks above the finalized head are indexed as soon as they are best, then checked again once
+/// finalized: a block replaced by a reorg has its entries dropped and the finalized one indexed
+/// instead. Clones share the same database.
+#[derive(Clone)]
+pub struct HistoryIndex {
+    db: sled::Db,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct WatchedAccount {
+    address: String,
+    /// `None` for accounts watched before the indexer started, indexed from its first block.
+    indexed_from: Option<u32>,
+}
+
+impl HistoryIndex {
+    pub fn open<P: core::convert::AsRef<std::path::Path>>(path: P) -> crate::Result<Self> {
+        let db = sled::open(path).map_err(index_error)?;
+
+        Ok(Self { db })
+    }
+
+    /// Index the movements of the account from the block after the best indexed one on, earlier
+    /// history is still read from the chain.
+    pub fn watch_address(&self, address: &str) -> crate::Result<()> {
+        let account = crate::compat::parse_account(address)?;
+        let account_key = prefixed_key(ACCOUNT_PREFIX, &[&account.0]);
+
+        if self.db.contains_key(&account_key).map_err(index_error)? {
+            return Ok(());
+        }
+
+        let watched_account = WatchedAccount {
+            address: address.to_string(),
+            // Blocks up to the best one were indexed without the account, even above the
+            // finalized head.
+            indexed_from: self.best_block()?.map(|best_block| best_block + 1),
+        };
+        let watched_account = serde_json::to_vec(&watched_account)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+        self.db
+            .insert(account_key, watched_account)
+            .map_err(index_error)?;
+
+        Ok(())
+    }
+
+    /// Stop indexing the account and drop its entries.
+    pub fn unwatch_address(&self, address: &str) -> crate::Result<()> {
+        let account = crate::compat::parse_account(address)?;
+        let mut batch = sled::Batch::default();
+        batch.remove(prefixed_key(ACCOUNT_PREFIX, &[&account.0]));
+
+        for entry in self
+            .db
+            .scan_prefix(prefixed_key(ENTRY_PREFIX, &[&account.0]))
+        {
+            let (entry_key, _) = entry.map_err(index_error)?;
+            let (block_number, entry_position) = entry_key[33..].split_at(4);
+            batch.remove(prefixed_key(
+                BLOCK_ENTRY_PREFIX,
+                &[block_number, &account.0, entry_position],
+            ));
+            batch.remove(entry_key);
+        }
+
+        self.db.apply_batch(batch).map_err(index_error)
+    }
+
+    /// Last indexed block that was checked against the finalized chain.
+    pub fn finalized_block(&self) -> crate::Result<Option<u32>> {
+        self.read_number(FINALIZED_BLOCK_KEY)
+    }
+
+    /// Highest indexed block, it may still be reverted when above [`Self::finalized_block`].
+    pub fn best_block(&self) -> crate::Result<Option<u32>> {
+        match self
+            .db
+            .scan_prefix([BLOCK_PREFIX])
+            .next_back()
+            .transpose()
+            .map_err(index_error)?
+        {
+            Some((block_key, _)) => Ok(Some(decode_number(&block_key[1..])?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Indexed entries of the account from `from_block` to `to_block`, both included, `None` when
+    /// the index doesn't cover the whole range or it goes past the finalized block.
+    pub fn account_history(
+        &self,
+        account_address: &str,
+        from_block: u32,
+        to_block: u32,
+    ) -> crate::Result<Option<Vec<crate::LedgerEntry>>> {
+        let account = crate::compat::parse_account(account_address)?;
+
+        if !self.covers(&account, from_block, to_block)? {
+            return Ok(None);
+        }
+
+        let account_prefix = prefixed_key(ENTRY_PREFIX, &[&account.0]);
+        let range_start = prefixed_key(ENTRY_PREFIX, &[&account.0, &from_block.to_be_bytes()]);
+        let mut entries = Vec::new();
+
+        for entry in self.db.range(range_start..) {
+            let (entry_key, entry) = entry.map_err(index_error)?;
+
+            if !entry_key.starts_with(&account_prefix)
+                || decode_number(&entry_key[33..37])? > to_block
+            {
+                break;
+            }
+
+            entries.push(decode_entry(&entry)?);
+        }
+
+        Ok(Some(entries))
+    }
+
+    /// Indexed entries of the finalized block for every account, `None` when the block isn't
+    /// finalized in the index yet or one of the accounts isn't indexed at that block.
+    pub(crate) fn finalized_entries_at(
+        &self,
+        accounts: &[subxt::utils::AccountId32],
+        block_number: u32,
+    ) -> crate::Result<Option<Vec<(subxt::utils::AccountId32, crate::LedgerEntry)>>> {
+        if self.finalized_block()? < Some(block_number) {
+            return Ok(None);
+        }
+
+        for account in accounts {
+            if !self.covers(account, block_number, block_number)? {
+                return Ok(None);
+            }
+        }
+
+        let mut entries = Vec::new();
+
+        for block_entry in self.db.scan_prefix(prefixed_key(
+            BLOCK_ENTRY_PREFIX,
+            &[&block_number.to_be_bytes()],
+        )) {
+            let (block_entry_key, _) = block_entry.map_err(index_error)?;
+            let account = subxt::utils::AccountId32(block_entry_key[5..37].try_into().unwrap());
+
+            if !accounts.contains(&account) {
+                continue;
+            }
+
+            let entry_key = prefixed_key(
+                ENTRY_PREFIX,
+                &[&account.0, &block_entry_key[1..5], &block_entry_key[37..]],
+            );
+
+            if let Some(entry) = self.db.get(entry_key).map_err(index_error)? {
+                entries.push((account, decode_entry(&entry)?));
+            }
+        }
+
+        Ok(Some(entries))
+    }
+
+    fn covers(
+        &self,
+        account: &subxt::utils::AccountId32,
+        from_block: u32,
+        to_block: u32,
+    ) -> crate::Result<bool> {
+        let indexed_from = match self.watched_account(account)? {
+            Some(WatchedAccount {
+                indexed_from: Some(indexed_from),
+                ..
+            }) => indexed_from,
+            Some(_) => match self.read_number(FIRST_BLOCK_KEY)? {
+                Some(first_block) => first_block,
+                None => return Ok(false),
+            },
+            None => return Ok(false),
+        };
+
+        // Entries above the finalized block may still be replaced by a reorg.
+        Ok(indexed_from <= from_block && Some(to_block) <= self.finalized_block()?)
+    }
+
+    fn watched_account(
+        &self,
+        account: &subxt::utils::AccountId32,
+    ) -> crate::Result<Option<WatchedAccount>> {
+        match self
+            .db
+            .get(prefixed_key(ACCOUNT_PREFIX, &[&account.0]))
+            .map_err(index_error)?
+        {
+            Some(watched_account) => serde_json::from_slice(&watched_account)
+                .map(Some)
+                .map_err(|err| crate::Error::OtherError(format!("Corrupted history index: {err}"))),
+            None => Ok(None),
+        }
+    }
+
+    fn indexed_accounts(&self, block_number: u32) -> crate::Result<Vec<subxt::utils::AccountId32>> {
+        let mut accounts = Vec::new();
+
+        for watched_account in self.db.scan_prefix([ACCOUNT_PREFIX]) {
+            let (account_key, watched_account) = watched_account.map_err(index_error)?;
+            let watched_account = serde_json::from_slice::<WatchedAccount>(&watched_account)
+                .map_err(|err| {
+                    crate::Error::OtherError(format!("Corrupted history index: {err}"))
+                })?;
+
+            if watched_account.indexed_from.unwrap_or_default() <= block_number {
+                accounts.push(subxt::utils::AccountId32(
+                    account_key[1..].try_into().unwrap(),
+                ));
+            }
+        }
+
+        Ok(accounts)
+    }
+
+    fn indexed_hash(&self, block_number: u32) -> crate::Result<Option<crate::BlockHash>> {
+        Ok(self
+            .db
+            .get(prefixed_key(BLOCK_PREFIX, &[&block_number.to_be_bytes()]))
+            .map_err(index_error)?
+            .map(|block_hash| crate::BlockHash::from_slice(&block_hash)))
+    }
+
+    fn read_number(&self, key: &[u8]) -> crate::Result<Option<u32>> {
+        match self.db.get(key).map_err(index_error)? {
+            Some(number) => Ok(Some(decode_number(&number)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn write_number(&self, key: &[u8], number: u32) -> crate::Result<()> {
+        self.db
+            .insert(key, number.to_be_bytes().to_vec())
+            .map_err(index_error)?;
+
+        Ok(())
+    }
+
+    /// Replace whatever was indexed at the height by the entries of the block.
+    async fn index_block(
+        &self,
+        client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+        runtime_mode: crate::RuntimeMode,
+        ss58_prefix: u16,
+        block_number: u32,
+        block_hash: crate::BlockHash,
+    ) -> crate::Result<()> {
+        let accounts = self.indexed_accounts(block_number)?;
+        let entries = crate::history::block_ledgers(
+            client,
+            runtime_mode,
+            ss58_prefix,
+            &accounts,
+            block_number,
+            block_hash,
+        )
+        .await?;
+        let mut batch = self.remove_block_batch(block_number)?;
+        batch.insert(
+            prefixed_key(BLOCK_PREFIX, &[&block_number.to_be_bytes()]),
+            block_hash.as_bytes(),
+        );
+
+        for (entry_position, (account, entry)) in entries.iter().enumerate() {
+            let entry_position = (entry_position as u32).to_be_bytes();
+            let entry = serde_json::to_vec(entry)
+                .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+            batch.insert(
+                prefixed_key(
+                    ENTRY_PREFIX,
+                    &[&account.0, &block_number.to_be_bytes(), &entry_position],
+                ),
+                entry,
+            );
+            batch.insert(
+                prefixed_key(
+                    BLOCK_ENTRY_PREFIX,
+                    &[&block_number.to_be_bytes(), &account.0, &entry_position],
+                ),
+                Vec::new(),
+            );
+        }
+
+        self.db.apply_batch(batch).map_err(index_error)
+    }
+
+    fn remove_block_batch(&self, block_number: u32) -> crate::Result<sled::Batch> {
+        let mut batch = sled::Batch::default();
+        batch.remove(prefixed_key(BLOCK_PREFIX, &[&block_number.to_be_bytes()]));
+
+        for block_entry in self.db.scan_prefix(prefixed_key(
+            BLOCK_ENTRY_PREFIX,
+            &[&block_number.to_be_bytes()],
+        )) {
+            let (block_entry_key, _) = block_entry.map_err(index_error)?;
+            batch.remove(prefixed_key(
+                ENTRY_PREFIX,
+                &[
+                    &block_entry_key[5..37],
+                    &block_entry_key[1..5],
+                    &block_entry_key[37..],
+                ],
+            ));
+            batch.remove(block_entry_key);
+        }
+
+        Ok(batch)
+    }
+
+    /// Bring the index up to the new best block.
+    ///
+    /// Finalized blocks are indexed by number, replacing the ones of an abandoned fork, then the
+    /// best chain is walked back from its head until it meets the indexed chain.
+    async fn follow_head(
+        &self,
+        client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+        runtime_mode: &std::sync::RwLock<crate::RuntimeMode>,
+        ss58_prefix: u16,
+        start_block: Option<u32>,
+        head_number: u32,
+        head_hash: crate::BlockHash,
+    ) -> crate::Result<()> {
+        let runtime_mode = crate::compat::compatible_runtime_mode(client, runtime_mode)?;
+        let finalized_hash = client.rpc().finalized_head().await?;
+        let finalized_number = client
+            .rpc()
+            .header(Some(finalized_hash))
+            .await?
+            .ok_or(crate::Error::BlockNotFound)?
+            .number;
+        let next_finalized = match self.finalized_block()? {
+            Some(finalized_block) => finalized_block + 1,
+            None => match self.read_number(FIRST_BLOCK_KEY)? {
+                Some(first_block) => first_block,
+                None => {
+                    let first_block = start_block.unwrap_or(finalized_number);
+                    self.write_number(FIRST_BLOCK_KEY, first_block)?;
+
+                    first_block
+                }
+            },
+        };
+
+        for block_number in next_finalized..=finalized_number {
+            let block_hash = client
+                .rpc()
+                .block_hash(Some(block_number.into()))
+                .await?
+                .ok_or(crate::Error::BlockNotFound)?;
+
+            if self.indexed_hash(block_number)? != Some(block_hash) {
+                self.index_block(client, runtime_mode, ss58_prefix, block_number, block_hash)
+                    .await?;
+            }
+
+            self.write_number(FINALIZED_BLOCK_KEY, block_number)?;
+        }
+
+        let finalized_block = match self.finalized_block()? {
+            Some(finalized_block) => finalized_block,
+            None => return Ok(()),
+        };
+        let mut branch = Vec::new();
+        let (mut block_number, mut block_hash) = (head_number, head_hash);
+
+        while block_number > finalized_block && self.indexed_hash(block_number)? != Some(block_hash)
+        {
+            branch.push((block_number, block_hash));
+            block_hash = client
+                .rpc()
+                .header(Some(block_hash))
+                .await?
+                .ok_or(crate::Error::BlockNotFound)?
+                .parent_hash;
+            block_number -= 1;
+        }
+
+        for (block_number, block_hash) in branch.into_iter().rev() {
+            self.index_block(client, runtime_mode, ss58_prefix, block_number, block_hash)
+                .await?;
+        }
+
+        while let Some(best_block) = self.best_block()? {
+            if best_block <= head_number.max(finalized_block) {
+                break;
+            }
+
+            let batch = self.remove_block_batch(best_block)?;
+            self.db.apply_batch(batch).map_err(index_error)?;
+        }
+
+        self.db.flush_async().await.map_err(index_error)?;
+
+        Ok(())
+    }
+}
+
+/// Follow the best blocks and keep the index on the chain until the subscription ends.
+pub(crate) async fn run_indexer(
+    client: subxt::OnlineClient<subxt::PolkadotConfig>,
+    runtime_mode: std::sync::Arc<std::sync::RwLock<crate::RuntimeMode>>,
+    ss58_prefix: u16,
+    history_index: HistoryIndex,
+    start_block: Option<u32>,
+) {
+    let mut best_heads = match client.blocks().subscribe_best().await {
+        Ok(best_heads) => best_heads,
+        Err(err) => {
+            nagara_logging::info!("History index can't follow the chain: {err}");
+
+            return;
+        }
+    };
+
+    while let Some(best_head) = futures::StreamExt::next(&mut best_heads).await {
+        let best_head = match best_head {
+            Ok(best_head) => best_head,
+            Err(err) => {
+                nagara_logging::info!("Best block notification failed: {err}");
+
+                continue;
+            }
+        };
+
+        if let Err(err) = history_index
+            .follow_head(
+                &client,
+                &runtime_mode,
+                ss58_prefix,
+                start_block,
+                best_head.number(),
+                best_head.hash(),
+            )
+            .await
+        {
+            nagara_logging::info!("History index update failed, retrying on the next block: {err}");
+        }
+    }
+}
+
+fn prefixed_key(prefix: u8, parts: &[&[u8]]) -> Vec<u8> {
+    let mut key = vec![prefix];
+
+    for part in parts {
+        key.extend_from_slice(part);
+    }
+
+    key
+}
+
+fn decode_number(number: &[u8]) -> crate::Result<u32> {
+    number
+        .try_into()
+        .map(u32::from_be_bytes)
+        .map_err(|_| crate::Error::OtherError("Corrupted history index".to_string()))
+}
+
+fn decode_entry(entry: &[u8]) -> crate::Result<crate::LedgerEntry> {
+    serde_json::from_slice(entry)
+        .map_err(|err| crate::Error::OtherError(format!("Corrupted history index: {err}")))
+}
+
+fn index_error(err: sled::Error) -> crate::Error {
+    crate::Error::OtherError(format!("History index failed: {err}"))
+}
+
+impl crate::WalletInstance {
+    /// Keep the index on the chain in the background and answer history and deposit queries from
+    /// it whenever it covers them.
+    ///
+    /// An empty index starts at `start_block`, or at the finalized head when `None`.
+    pub fn start_history_index(&mut self, history_index: HistoryIndex, start_block: Option<u32>) {
+        let indexer = crate::task::spawn(run_indexer(
+            self.client.clone(),
+            self.runtime_mode.clone(),
+            self.network.ss58_prefix,
+            history_index.clone(),
+            start_block,
+        ));
+        self.background_tasks.push(indexer);
+        self.history_index = Some(history_index);
+    }
+}
@@ -0,0 +1,741 @@
+/// Where a journaled transaction is, [`JournalStatus::Signed`] until the node is known to have
+/// received it.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JournalStatus {
+    /// Recorded before its broadcast, which may not have reached the node.
+    Signed,
+    Pending,
+    InBlock {
+        block_hash: crate::BlockHash,
+    },
+    Finalized {
+        block_hash: crate::BlockHash,
+        success: bool,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+impl JournalStatus {
+    /// Whether the transaction may still be included, [`crate::WalletInstance::recover_journal`]
+    /// reconciles these ones.
+    pub fn is_unsettled(&self) -> bool {
+        matches!(self, Self::Signed | Self::Pending | Self::InBlock { .. })
+    }
+}
+
+impl From<crate::TransactionStatus> for JournalStatus {
+    fn from(value: crate::TransactionStatus) -> Self {
+        match value {
+            crate::TransactionStatus::Pending => Self::Pending,
+            crate::TransactionStatus::InBlock { block_hash } => Self::InBlock { block_hash },
+            crate::TransactionStatus::Finalized {
+                block_hash,
+                success,
+            } => Self::Finalized {
+                block_hash,
+                success,
+            },
+            crate::TransactionStatus::Failed { reason } => Self::Failed { reason },
+        }
+    }
+}
+
+/// A transaction recorded with its signed extrinsic before being broadcast.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct JournalEntry {
+    /// Key given by the caller, the same payment is never signed twice under it. Transactions
+    /// submitted without one are keyed by their `0x` prefixed extrinsic hash.
+    pub idempotency_key: String,
+    pub sender_address: String,
+    /// Transfers of the payment, a single one for a transfer, none for other calls.
+    pub payouts: Vec<crate::Payout>,
+    pub extrinsic_hash: crate::ExtrinsicHash,
+    pub nonce: u64,
+    /// Number of the best block when the extrinsic was signed, it can't be included before.
+    pub signed_at_block: u32,
+    /// Signed extrinsic as broadcast, length prefix included.
+    #[serde(with = "hex::serde")]
+    pub signed_extrinsic: Vec<u8>,
+    pub status: JournalStatus,
+}
+
+/// Journal of every transaction the wallet broadcasts, kept in a JSON file rewritten on every
+/// change.
+pub struct TransactionJournal {
+    journal_path: std::path::PathBuf,
+    entries: std::sync::Mutex<Vec<JournalEntry>>,
+    /// Locks of the idempotency keys being submitted, held from the idempotency check until the
+    /// entry is recorded.
+    submissions: std::sync::Mutex<
+        std::collections::HashMap<String, std::sync::Arc<futures::lock::Mutex<()>>>,
+    >,
+}
+
+impl TransactionJournal {
+    /// Read the journal file, a missing file gives an empty journal.
+    pub fn open<P: core::convert::AsRef<std::path::Path>>(journal_path: P) -> crate::Result<Self> {
+        let journal_path = journal_path.as_ref().to_path_buf();
+        let entries = if journal_path.exists() {
+            let journal = std::fs::read(&journal_path)
+                .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+            serde_json::from_slice::<Vec<JournalEntry>>(&journal).map_err(|err| {
+                crate::Error::OtherError(format!("Corrupted transaction journal: {err}"))
+            })?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            journal_path,
+            entries: std::sync::Mutex::new(entries),
+            submissions: std::sync::Mutex::new(std::collections::HashMap::new()),
+        })
+    }
+
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn entry(&self, idempotency_key: &str) -> Option<JournalEntry> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|entry| entry.idempotency_key == idempotency_key)
+            .cloned()
+    }
+
+    /// Replace the journal file, synced to disk with its directory before returning so that an
+    /// entry recorded before a broadcast survives a crash right after it.
+    fn save(&self, entries: &[JournalEntry]) -> crate::Result<()> {
+        let journal =
+            serde_json::to_vec(entries).map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        replace_file(&self.journal_path, &journal)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+
+    fn submission(&self, idempotency_key: &str) -> std::sync::Arc<futures::lock::Mutex<()>> {
+        self.submissions
+            .lock()
+            .unwrap()
+            .entry(idempotency_key.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Forget the lock of the key once no submission holds it.
+    fn end_submission(&self, idempotency_key: &str) {
+        let mut submissions = self.submissions.lock().unwrap();
+
+        if matches!(
+            submissions.get(idempotency_key),
+            Some(submission) if std::sync::Arc::strong_count(submission) == 1
+        ) {
+            submissions.remove(idempotency_key);
+        }
+    }
+
+    fn insert(&self, entry: JournalEntry) -> crate::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(entry);
+
+        self.save(&entries)
+    }
+
+    pub(crate) fn set_status(
+        &self,
+        extrinsic_hash: crate::ExtrinsicHash,
+        status: JournalStatus,
+    ) -> crate::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries
+            .iter_mut()
+            .find(|entry| entry.extrinsic_hash == extrinsic_hash)
+        else {
+            return Ok(());
+        };
+
+        if entry.status == status {
+            return Ok(());
+        }
+
+        entry.status = status;
+
+        self.save(&entries)
+    }
+}
+
+impl crate::WalletInstance {
+    /// Journal every transaction before its broadcast, with the statuses of the ones followed by
+    /// the wallet, see [`Self::recover_journal`].
+    pub fn set_journal(&mut self, journal: TransactionJournal) {
+        let journal = std::sync::Arc::new(journal);
+        self.transactions.set_journal(journal.clone());
+        self.journal = Some(journal);
+    }
+
+    pub fn journal_entries(&self) -> Vec<JournalEntry> {
+        self.journal
+            .as_ref()
+            .map_or_else(Vec::new, |journal| journal.entries())
+    }
+
+    /// Submit a transfer once per idempotency key, the entry already recorded under the key is
+    /// returned instead of signing the transfer again.
+    pub async fn submit_transfer_idempotent(
+        &self,
+        idempotency_key: &str,
+        sender_address: &str,
+        recipient_address: &str,
+        balance: u128,
+        options: &crate::TxOptions,
+    ) -> crate::Result<JournalEntry> {
+        let call = self.transfer_call(recipient_address, balance)?;
+        let payouts = vec![crate::Payout {
+            recipient_address: recipient_address.to_string(),
+            amount: balance,
+        }];
+
+        self.submit_journaled(idempotency_key, sender_address, payouts, &call, options)
+            .await
+    }
+
+    /// Submit a batch payout once per idempotency key, see [`Self::submit_transfer_idempotent`].
+    pub async fn submit_payouts_idempotent(
+        &self,
+        idempotency_key: &str,
+        sender_address: &str,
+        payouts: &[crate::Payout],
+        options: &crate::TxOptions,
+    ) -> crate::Result<JournalEntry> {
+        let call = self.payouts_call(payouts)?;
+
+        self.submit_journaled(
+            idempotency_key,
+            sender_address,
+            payouts.to_vec(),
+            &call,
+            options,
+        )
+        .await
+    }
+
+    async fn submit_journaled(
+        &self,
+        idempotency_key: &str,
+        sender_address: &str,
+        payouts: Vec<crate::Payout>,
+        call: &crate::extrinsic::RawCall,
+        options: &crate::TxOptions,
+    ) -> crate::Result<JournalEntry> {
+        let journal = self.journal.as_ref().ok_or_else(|| {
+            crate::Error::InvalidArgument("Idempotency keys need a transaction journal".to_string())
+        })?;
+        let submission = journal.submission(idempotency_key);
+        let entry = {
+            let _submission = submission.lock().await;
+
+            self.submit_journaled_locked(
+                journal,
+                idempotency_key,
+                sender_address,
+                payouts,
+                call,
+                options,
+            )
+            .await
+        };
+        drop(submission);
+        journal.end_submission(idempotency_key);
+
+        entry
+    }
+
+    async fn submit_journaled_locked(
+        &self,
+        journal: &TransactionJournal,
+        idempotency_key: &str,
+        sender_address: &str,
+        payouts: Vec<crate::Payout>,
+        call: &crate::extrinsic::RawCall,
+        options: &crate::TxOptions,
+    ) -> crate::Result<JournalEntry> {
+        if let Some(entry) = journal.entry(idempotency_key) {
+            if entry.sender_address != sender_address || entry.payouts != payouts {
+                return Err(crate::Error::InvalidArgument(format!(
+                    "Idempotency key {idempotency_key} was used for another payment"
+                )));
+            }
+
+            // The broadcast of a signed entry may have failed, it is sent again unless the chain
+            // already has it.
+            if entry.status == JournalStatus::Signed {
+                let mut scan = self.scan_journal(std::slice::from_ref(&entry)).await?;
+                let status = self.reconcile_entry(&entry, &mut scan).await?;
+                journal.set_status(entry.extrinsic_hash, status.clone())?;
+
+                return Ok(JournalEntry { status, ..entry });
+            }
+
+            return Ok(entry);
+        }
+
+        let (entry, progress) = self
+            .broadcast(
+                Some(idempotency_key),
+                sender_address,
+                &payouts,
+                call,
+                options,
+            )
+            .await?;
+        self.transactions.track(progress);
+
+        // Following the transaction may have updated its status already.
+        journal
+            .entry(idempotency_key)
+            .or(entry)
+            .ok_or_else(|| crate::Error::OtherError("Journal entry is missing".to_string()))
+    }
+
+    /// Sign and broadcast, the signed extrinsic being recorded in the journal beforehand when one
+    /// is set. Entries submitted without an idempotency key are keyed by their `0x` prefixed
+    /// extrinsic hash.
+    pub(crate) async fn broadcast<Call: subxt::tx::TxPayload>(
+        &self,
+        idempotency_key: Option<&str>,
+        sender_address: &str,
+        payouts: &[crate::Payout],
+        call: &Call,
+        options: &crate::TxOptions,
+    ) -> crate::Result<(Option<JournalEntry>, crate::tx_options::TxProgress)> {
+        let _sender_lock = self.sender_lock(sender_address)?.lock().await;
+        let signed_at_block = self.chain_heads().best_number();
+        let (extrinsic, reservation) = self.sign_authorized(sender_address, call, options).await?;
+        self.validate_extrinsic(&extrinsic, options.get_dry_run())
+            .await?;
+        let entry = match &self.journal {
+            Some(journal) => {
+                let envelope = crate::extrinsic::ExtrinsicEnvelope::decode(extrinsic.encoded())?;
+                let entry = JournalEntry {
+                    idempotency_key: idempotency_key.map_or_else(
+                        || format!("0x{}", hex::encode(envelope.hash)),
+                        str::to_string,
+                    ),
+                    sender_address: sender_address.to_string(),
+                    payouts: payouts.to_vec(),
+                    extrinsic_hash: envelope.hash,
+                    nonce: envelope.signature.map_or(0, |signature| signature.nonce),
+                    signed_at_block,
+                    signed_extrinsic: extrinsic.encoded().to_vec(),
+                    status: JournalStatus::Signed,
+                };
+                journal.insert(entry.clone())?;
+
+                Some(entry)
+            }
+            None => None,
+        };
+        let progress = extrinsic.submit_and_watch().await?;
+
+        if let Some(reservation) = reservation {
+            reservation.commit();
+        }
+
+        // Entries of the transactions their callers follow are settled by `recover_journal`.
+        if let (Some(journal), Some(entry)) = (&self.journal, &entry) {
+            if let Err(err) = journal.set_status(entry.extrinsic_hash, JournalStatus::Pending) {
+                nagara_logging::info!(
+                    "Journal of {:?} wasn't updated: {err}",
+                    entry.extrinsic_hash
+                );
+            }
+        }
+
+        Ok((entry, progress))
+    }
+
+    /// Reconcile the unsettled entries of the journal against the chain, to run on startup.
+    ///
+    /// An entry found in a block gets its outcome and one still in the pool stays pending. The
+    /// others are broadcast again with the same signed bytes, which can't pay twice, or fail once
+    /// another transaction used their nonce. Entries the chain refuses for other reasons stay
+    /// pending until the next recovery. Returns the entries reconciled.
+    pub async fn recover_journal(&self) -> crate::Result<Vec<JournalEntry>> {
+        let Some(journal) = &self.journal else {
+            return Ok(Vec::new());
+        };
+        let unsettled_entries = journal
+            .entries()
+            .into_iter()
+            .filter(|entry| entry.status.is_unsettled())
+            .collect::<Vec<_>>();
+        let mut scan = self.scan_journal(&unsettled_entries).await?;
+        let mut recovered_entries = Vec::with_capacity(unsettled_entries.len());
+
+        for entry in unsettled_entries {
+            let status = self.reconcile_entry(&entry, &mut scan).await?;
+            journal.set_status(entry.extrinsic_hash, status.clone())?;
+
+            recovered_entries.push(JournalEntry { status, ..entry });
+        }
+
+        Ok(recovered_entries)
+    }
+
+    /// Scan the blocks from the oldest of the entries up to the best one.
+    async fn scan_journal(&self, entries: &[JournalEntry]) -> crate::Result<JournalScan> {
+        let mut scan = JournalScan::new(entries);
+        self.scan_journal_blocks(&mut scan, &self.chain_heads())
+            .await?;
+
+        Ok(scan)
+    }
+
+    /// Scan the blocks not scanned yet up to the best one of `chain_heads`, each block once for
+    /// all of the entries.
+    async fn scan_journal_blocks(
+        &self,
+        scan: &mut JournalScan,
+        chain_heads: &crate::ChainHeads,
+    ) -> crate::Result<()> {
+        for block_number in scan.next_block..=chain_heads.best_number() {
+            let block_info = self.block_info(block_number.into()).await?;
+
+            for extrinsic in &block_info.extrinsics {
+                if !scan.extrinsic_hashes.contains(&extrinsic.hash) {
+                    continue;
+                }
+
+                let status = if block_number > chain_heads.finalized_number() {
+                    JournalStatus::InBlock {
+                        block_hash: block_info.hash,
+                    }
+                } else {
+                    JournalStatus::Finalized {
+                        block_hash: block_info.hash,
+                        success: extrinsic.success.unwrap_or(false),
+                    }
+                };
+                scan.found.insert(extrinsic.hash, status);
+            }
+
+            scan.next_block = block_number + 1;
+        }
+
+        Ok(())
+    }
+
+    async fn reconcile_entry(
+        &self,
+        entry: &JournalEntry,
+        scan: &mut JournalScan,
+    ) -> crate::Result<JournalStatus> {
+        if let Some(status) = scan.found.get(&entry.extrinsic_hash) {
+            return Ok(status.clone());
+        }
+
+        let in_pool = self
+            .pending_transactions(&entry.sender_address)
+            .await?
+            .iter()
+            .any(|pending_transaction| pending_transaction.extrinsic_hash == entry.extrinsic_hash);
+
+        if in_pool {
+            return Ok(JournalStatus::Pending);
+        }
+
+        let extrinsic = crate::tx_options::SignedExtrinsic::from_bytes(
+            self.client.clone(),
+            entry.signed_extrinsic.clone(),
+        );
+
+        match self.validate_extrinsic(&extrinsic, false).await {
+            Ok(()) => {}
+            Err(
+                err @ (crate::Error::InvalidTransaction(_) | crate::Error::UnknownTransaction(_)),
+            ) => {
+                // The extrinsic may have been included since the scan, making it stale. The nonce
+                // is read before the blocks so that they include whatever used it.
+                let sender_account = crate::compat::parse_account(&entry.sender_address)?;
+                let next_nonce = self.client.tx().account_nonce(&sender_account).await?;
+                let chain_heads = crate::head::fetch_chain_heads(&self.client).await?;
+                self.scan_journal_blocks(scan, &chain_heads).await?;
+
+                if let Some(status) = scan.found.get(&entry.extrinsic_hash) {
+                    return Ok(status.clone());
+                }
+
+                if next_nonce > entry.nonce {
+                    return Ok(JournalStatus::Failed {
+                        reason: err.to_string(),
+                    });
+                }
+
+                return Ok(JournalStatus::Pending);
+            }
+            Err(err) => return Err(err),
+        }
+
+        let progress = extrinsic.submit_and_watch().await?;
+        self.transactions.track(progress);
+
+        Ok(JournalStatus::Pending)
+    }
+}
+
+/// Blocks scanned for journaled extrinsics, with the statuses of the ones found.
+struct JournalScan {
+    extrinsic_hashes: std::collections::HashSet<crate::ExtrinsicHash>,
+    next_block: u32,
+    found: std::collections::HashMap<crate::ExtrinsicHash, JournalStatus>,
+}
+
+impl JournalScan {
+    /// Start at the oldest block the entries may be in.
+    fn new(entries: &[JournalEntry]) -> Self {
+        Self {
+            extrinsic_hashes: entries.iter().map(|entry| entry.extrinsic_hash).collect(),
+            next_block: entries
+                .iter()
+                .map(|entry| entry.signed_at_block)
+                .min()
+                .unwrap_or(u32::MAX),
+            found: std::collections::HashMap::new(),
+        }
+    }
+}
+
+/// Replace the file through a synced temporary file, so that a crash leaves either the old or the
+/// new contents.
+pub(crate) fn replace_file(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    let path_tmp = path.with_extension("tmp");
+    let mut file = std::fs::File::create(&path_tmp)?;
+    std::io::Write::write_all(&mut file, contents)?;
+    file.sync_all()?;
+    std::fs::rename(&path_tmp, path)?;
+
+    sync_directory(path)
+}
+
+/// Sync the directory of the file, which the rename changed.
+#[cfg(unix)]
+fn sync_directory(path: &std::path::Path) -> std::io::Result<()> {
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => std::path::Path::new("."),
+    };
+
+    std::fs::File::open(directory)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &std::path::Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    fn journal_path(name: &str) -> std::path::PathBuf {
+        let journal_path =
+            std::env::temp_dir().join(format!("nagara-journal-{}-{name}.json", std::process::id()));
+        let _ = std::fs::remove_file(&journal_path);
+
+        journal_path
+    }
+
+    fn entry(
+        idempotency_key: &str,
+        extrinsic_hash: u8,
+        signed_at_block: u32,
+    ) -> super::JournalEntry {
+        super::JournalEntry {
+            idempotency_key: idempotency_key.to_string(),
+            sender_address: BOB.to_string(),
+            payouts: vec![crate::Payout {
+                recipient_address: BOB.to_string(),
+                amount: 10,
+            }],
+            extrinsic_hash: crate::ExtrinsicHash::repeat_byte(extrinsic_hash),
+            nonce: 0,
+            signed_at_block,
+            signed_extrinsic: vec![0x04, extrinsic_hash],
+            status: super::JournalStatus::Signed,
+        }
+    }
+
+    #[test]
+    fn journal_reloads_entries_with_their_statuses() {
+        let journal_path = journal_path("reload");
+        let journal = super::TransactionJournal::open(&journal_path).unwrap();
+        assert!(journal.entries().is_empty());
+
+        journal.insert(entry("first", 1, 5)).unwrap();
+        journal.insert(entry("second", 2, 7)).unwrap();
+        let in_block = super::JournalStatus::InBlock {
+            block_hash: crate::BlockHash::repeat_byte(9),
+        };
+        journal
+            .set_status(crate::ExtrinsicHash::repeat_byte(2), in_block.clone())
+            .unwrap();
+        // Unknown extrinsics are ignored.
+        journal
+            .set_status(
+                crate::ExtrinsicHash::repeat_byte(3),
+                super::JournalStatus::Pending,
+            )
+            .unwrap();
+
+        let journal = super::TransactionJournal::open(&journal_path).unwrap();
+        assert_eq!(journal.entries().len(), 2);
+        assert_eq!(journal.entry("first"), Some(entry("first", 1, 5)));
+        assert_eq!(journal.entry("second").unwrap().status, in_block);
+        assert_eq!(journal.entry("third"), None);
+
+        std::fs::remove_file(&journal_path).unwrap();
+    }
+
+    #[test]
+    fn corrupted_journal_is_refused() {
+        let journal_path = journal_path("corrupted");
+        std::fs::write(&journal_path, b"[{").unwrap();
+
+        assert!(matches!(
+            super::TransactionJournal::open(&journal_path),
+            Err(crate::Error::OtherError(message)) if message.contains("Corrupted")
+        ));
+
+        std::fs::remove_file(&journal_path).unwrap();
+    }
+
+    #[test]
+    fn submissions_are_locked_per_idempotency_key() {
+        let journal = super::TransactionJournal::open(journal_path("locks")).unwrap();
+        let first = journal.submission("first");
+        let _first_guard = first.try_lock().unwrap();
+
+        assert!(journal.submission("first").try_lock().is_none());
+        assert!(journal.submission("second").try_lock().is_some());
+
+        // Only the held lock is kept.
+        journal.end_submission("second");
+        assert_eq!(journal.submissions.lock().unwrap().len(), 1);
+        journal.end_submission("first");
+        assert_eq!(journal.submissions.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn scan_starts_at_the_oldest_entry() {
+        let scan = super::JournalScan::new(&[entry("first", 1, 7), entry("second", 2, 5)]);
+
+        assert_eq!(scan.next_block, 5);
+        assert_eq!(scan.extrinsic_hashes.len(), 2);
+        assert!(super::JournalScan::new(&[]).next_block > 0);
+    }
+
+    async fn dev_wallet(journal_path: &std::path::Path) -> (crate::WalletInstance, String) {
+        let rpc_url = std::env::var("NAGARA_WALLET_TEST_RPC_URL")
+            .unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string());
+        let secret = std::env::var("NAGARA_WALLET_TEST_SECRET")
+            .expect("NAGARA_WALLET_TEST_SECRET holds the secret of a funded account");
+        let mut wallet = crate::WalletInstance::create_with_network(
+            crate::NetworkConfig::local()
+                .with_rpc_urls(&[rpc_url])
+                .with_dynamic_fallback(true),
+        )
+        .await
+        .unwrap();
+        wallet.set_journal(super::TransactionJournal::open(journal_path).unwrap());
+        let sender_address = wallet.add_account(&secret, true).unwrap();
+
+        (wallet, sender_address)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a dev node and NAGARA_WALLET_TEST_SECRET"]
+    async fn idempotency_key_signs_the_payment_once() {
+        let journal_path = journal_path("dedupe");
+        let (wallet, sender_address) = dev_wallet(&journal_path).await;
+        let options = crate::TxOptions::default();
+
+        let first = wallet
+            .submit_transfer_idempotent("payment", &sender_address, BOB, 10, &options)
+            .await
+            .unwrap();
+        let second = wallet
+            .submit_transfer_idempotent("payment", &sender_address, BOB, 10, &options)
+            .await
+            .unwrap();
+        assert_eq!(first.extrinsic_hash, second.extrinsic_hash);
+
+        let other_payment = wallet
+            .submit_transfer_idempotent("payment", &sender_address, BOB, 20, &options)
+            .await;
+        assert!(matches!(
+            other_payment,
+            Err(crate::Error::InvalidArgument(_))
+        ));
+
+        let journal = super::TransactionJournal::open(&journal_path).unwrap();
+        assert_eq!(journal.entries().len(), 1);
+
+        std::fs::remove_file(&journal_path).unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a dev node and NAGARA_WALLET_TEST_SECRET"]
+    async fn recovery_settles_an_included_entry() {
+        let journal_path = journal_path("recover");
+        let entry = {
+            let (wallet, sender_address) = dev_wallet(&journal_path).await;
+
+            wallet
+                .submit_transfer_idempotent(
+                    "payment",
+                    &sender_address,
+                    BOB,
+                    10,
+                    &crate::TxOptions::default(),
+                )
+                .await
+                .unwrap()
+        };
+
+        // As if the wallet stopped before learning the node had the extrinsic.
+        super::TransactionJournal::open(&journal_path)
+            .unwrap()
+            .set_status(entry.extrinsic_hash, super::JournalStatus::Signed)
+            .unwrap();
+
+        let (wallet, _) = dev_wallet(&journal_path).await;
+        let status = loop {
+            let recovered_entries = wallet.recover_journal().await.unwrap();
+            assert_eq!(recovered_entries.len(), 1);
+
+            match &recovered_entries[0].status {
+                super::JournalStatus::Pending => {
+                    tokio::time::sleep(core::time::Duration::from_secs(1)).await
+                }
+                status => break status.clone(),
+            }
+        };
+
+        assert!(
+            matches!(
+                status,
+                super::JournalStatus::InBlock { .. } | super::JournalStatus::Finalized { .. }
+            ),
+            "{status:?}"
+        );
+        assert_eq!(wallet.journal_entries()[0].status, status);
+
+        std::fs::remove_file(&journal_path).unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+/// A private key hex or mnemonic encrypted with a key derived from the passphrase of the keystore.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct KeystoreEntry {
+    pub address: String,
+    pub use_schnorrkel: bool,
+    #[serde(with = "hex::serde")]
+    pub salt: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub nonce: Vec<u8>,
+    /// ChaCha20-Poly1305 ciphertext of the secret, its tag included.
+    #[serde(with = "hex::serde")]
+    pub ciphertext: Vec<u8>,
+}
+
+/// Accounts kept encrypted in a JSON file, unlocked into a wallet with
+/// [`crate::WalletInstance::unlock_keystore`].
+///
+/// Each secret has its own salt, the encryption key is derived from the passphrase with scrypt.
+pub struct Keystore {
+    keystore_path: std::path::PathBuf,
+    entries: Vec<KeystoreEntry>,
+}
+
+impl Keystore {
+    const SCRYPT_LOG_N: u8 = 15;
+    const SCRYPT_R: u32 = 8;
+    const SCRYPT_P: u32 = 1;
+
+    /// Read the keystore file, a missing file gives an empty keystore.
+    pub fn open<P: core::convert::AsRef<std::path::Path>>(keystore_path: P) -> crate::Result<Self> {
+        let keystore_path = keystore_path.as_ref().to_path_buf();
+        let entries = if keystore_path.exists() {
+            let keystore = std::fs::read(&keystore_path)
+                .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+            serde_json::from_slice::<Vec<KeystoreEntry>>(&keystore)
+                .map_err(|err| crate::Error::OtherError(format!("Corrupted keystore: {err}")))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            keystore_path,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &[KeystoreEntry] {
+        &self.entries
+    }
+
+    /// Encrypt the secret of an account and save it, returns the address of the account. The
+    /// passphrase must be the one of the accounts already in the keystore.
+    pub fn add(
+        &mut self,
+        secret_str: &str,
+        use_schnorrkel: bool,
+        passphrase: &str,
+    ) -> crate::Result<String> {
+        let address =
+            crate::OwnedAccount::new_from_str(secret_str, use_schnorrkel)?.get_main_address();
+
+        if self.entries.iter().any(|entry| entry.address == address) {
+            return Err(crate::Error::InvalidArgument(format!(
+                "{address} is already in the keystore"
+            )));
+        }
+
+        // One passphrase unlocks the whole keystore.
+        if let Some(entry) = self.entries.first() {
+            Self::decrypt(entry, passphrase)?;
+        }
+
+        let mut salt = vec![0u8; 16];
+        let mut nonce = vec![0u8; 12];
+        getrandom::getrandom(&mut salt)
+            .and_then(|_| getrandom::getrandom(&mut nonce))
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+        let cipher = Self::cipher(passphrase, &salt)?;
+        let ciphertext = chacha20poly1305::aead::Aead::encrypt(
+            &cipher,
+            chacha20poly1305::Nonce::from_slice(&nonce),
+            secret_str.as_bytes(),
+        )
+        .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+        self.entries.push(KeystoreEntry {
+            address: address.clone(),
+            use_schnorrkel,
+            salt,
+            nonce,
+            ciphertext,
+        });
+        self.save()?;
+
+        Ok(address)
+    }
+
+    /// Remove an account, `false` when the keystore doesn't have it.
+    pub fn remove(&mut self, address: &str) -> crate::Result<bool> {
+        let entries_len = self.entries.len();
+        self.entries.retain(|entry| entry.address != address);
+
+        if self.entries.len() == entries_len {
+            return Ok(false);
+        }
+
+        self.save()?;
+
+        Ok(true)
+    }
+
+    fn cipher(passphrase: &str, salt: &[u8]) -> crate::Result<chacha20poly1305::ChaCha20Poly1305> {
+        let params = scrypt::Params::new(Self::SCRYPT_LOG_N, Self::SCRYPT_R, Self::SCRYPT_P, 32)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+        let mut key = [0u8; 32];
+        scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        <chacha20poly1305::ChaCha20Poly1305 as chacha20poly1305::KeyInit>::new_from_slice(&key)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+
+    fn decrypt(entry: &KeystoreEntry, passphrase: &str) -> crate::Result<String> {
+        let cipher = Self::cipher(passphrase, &entry.salt)?;
+        let secret = chacha20poly1305::aead::Aead::decrypt(
+            &cipher,
+            chacha20poly1305::Nonce::from_slice(&entry.nonce),
+            entry.ciphertext.as_slice(),
+        )
+        .map_err(|_| {
+            crate::Error::InvalidArgument(format!(
+                "Wrong passphrase for {} in the keystore",
+                entry.address
+            ))
+        })?;
+
+        String::from_utf8(secret).map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+
+    fn save(&self) -> crate::Result<()> {
+        let keystore_path_tmp = self.keystore_path.with_extension("tmp");
+        let keystore = serde_json::to_vec_pretty(&self.entries)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        std::fs::write(&keystore_path_tmp, keystore)
+            .and_then(|_| std::fs::rename(&keystore_path_tmp, &self.keystore_path))
+            .map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+}
+
+impl crate::WalletInstance {
+    /// Decrypt every account of the keystore into the wallet, returns their addresses.
+    pub fn unlock_keystore(
+        &mut self,
+        keystore: &Keystore,
+        passphrase: &str,
+    ) -> crate::Result<Vec<String>> {
+        keystore
+            .entries
+            .iter()
+            .map(|entry| {
+                let secret = Keystore::decrypt(entry, passphrase)?;
+
+                self.add_account(&secret, entry.use_schnorrkel)
+            })
+            .collect()
+    }
+}
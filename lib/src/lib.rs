@@ -1,9 +1,69 @@
 #[cfg(all(feature = "default", feature = "wasm32"))]
 compile_error!("Feature \"default\" can't be combined with \"wasm32\".");
+#[cfg(all(feature = "index-db", feature = "wasm32"))]
+compile_error!("Feature \"index-db\" can't be combined with \"wasm32\".");
 
+mod balance;
+mod block;
+mod compat;
+mod deposit;
+mod dynamic;
+mod events;
+mod extrinsic;
+#[cfg(feature = "default")]
+mod ffi;
+mod head;
+mod historical;
+mod history;
+#[cfg(feature = "index-db")]
+mod index_db;
+mod journal;
+mod keystore;
 pub(crate) mod metadata;
+mod network;
+mod payout;
+mod pending;
+mod policy;
+mod report;
+mod rpc;
+mod status;
+mod task;
+mod tx_options;
+mod tx_status;
+mod upgrade;
+mod validity;
+#[cfg(feature = "wasm32")]
+mod wasm;
+
+pub use balance::BalanceInfo;
+pub use block::{BlockInfo, EventInfo, ExtrinsicInfo};
+pub use compat::RuntimeMode;
+pub use deposit::{DepositRecord, DepositScanner};
+pub use extrinsic::{
+    CallInfo, DecodedExtrinsic, DecodedSignature, Era, ExtrinsicHash, SignatureKind,
+};
+#[cfg(all(feature = "headers", feature = "default"))]
+pub use ffi::generate_headers;
+pub use head::{BlockHeader, ChainHeads};
+pub use historical::BlockId;
+pub use history::{AccountLedger, LedgerEntry, LedgerKind, LedgerStatus};
+#[cfg(feature = "index-db")]
+pub use index_db::HistoryIndex;
+pub use journal::{JournalEntry, JournalStatus, TransactionJournal};
+pub use keystore::{Keystore, KeystoreEntry};
+pub use network::NetworkConfig;
+pub use payout::Payout;
+pub use pending::{PendingTransaction, ReplacementOutcome};
+pub use policy::{PolicyViolation, SpendingPolicies, SpendingPolicy};
+pub use report::{AccountingReport, ReportPeriod, ReportRow, ReportTotal};
+pub use status::{ChainStatus, FinalityAlert, PendingAuthorityChange};
+pub use tx_options::TxOptions;
+pub use tx_status::TransactionStatus;
+pub use upgrade::RuntimeUpgrade;
+pub use validity::{InvalidTransaction, UnknownTransaction};
 
 pub type Result<T> = core::result::Result<T, Error>;
+pub type BlockHash = subxt::utils::H256;
 
 #[derive(core::fmt::Debug, thiserror::Error)]
 pub enum Error {
@@ -15,6 +75,40 @@ pub enum Error {
     AccountNotFound,
     #[error("Maximum account custody reached")]
     AccountFull,
+    #[error("Block not found")]
+    BlockNotFound,
+    #[error("Transaction not found in the pool")]
+    TransactionNotPending,
+    #[error("Replacement tip must be higher than the pending transaction tip")]
+    ReplacementUnderpriced,
+    #[error("Replacement was dropped from the pool")]
+    ReplacementDropped,
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(InvalidTransaction),
+    #[error("Unknown transaction validity: {0}")]
+    UnknownTransaction(UnknownTransaction),
+    #[error("No healthy RPC endpoint available")]
+    NoHealthyEndpoint,
+    #[error("Node is on another chain, expected genesis {expected:?} but got {actual:?}")]
+    GenesisMismatch {
+        expected: BlockHash,
+        actual: BlockHash,
+    },
+    #[error(
+        "Runtime {spec_version}/{transaction_version} is incompatible with the built-in metadata"
+    )]
+    IncompatibleRuntime {
+        spec_version: u32,
+        transaction_version: u32,
+    },
+    #[error("{0} not found in the runtime metadata")]
+    MetadataNotFound(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Extrinsic not found in the block")]
+    ExtrinsicNotFound,
+    #[error("Rejected by the spending policy: {0}")]
+    PolicyViolation(PolicyViolation),
 }
 
 impl From<crate::Error> for i32 {
@@ -24,6 +118,19 @@ impl From<crate::Error> for i32 {
             crate::Error::OtherError(_) => -2,
             crate::Error::AccountNotFound => -3,
             crate::Error::AccountFull => -4,
+            crate::Error::BlockNotFound => -5,
+            crate::Error::TransactionNotPending => -6,
+            crate::Error::ReplacementUnderpriced => -7,
+            crate::Error::ReplacementDropped => -8,
+            crate::Error::InvalidTransaction(_) => -9,
+            crate::Error::UnknownTransaction(_) => -10,
+            crate::Error::NoHealthyEndpoint => -11,
+            crate::Error::GenesisMismatch { .. } => -12,
+            crate::Error::IncompatibleRuntime { .. } => -13,
+            crate::Error::MetadataNotFound(_) => -14,
+            crate::Error::InvalidArgument(_) => -15,
+            crate::Error::ExtrinsicNotFound => -16,
+            crate::Error::PolicyViolation(_) => -17,
         }
     }
 }
@@ -114,31 +221,137 @@ impl subxt::tx::Signer<subxt::PolkadotConfig> for OwnedAccount {
 
 pub struct WalletInstance {
     client: subxt::OnlineClient<subxt::PolkadotConfig>,
+    rpc_client: rpc::FailoverRpcClient,
+    network: NetworkConfig,
+    runtime_mode: std::sync::Arc<std::sync::RwLock<RuntimeMode>>,
+    runtime_upgrades: std::sync::Arc<task::Listeners<RuntimeUpgrade>>,
+    chain_heads: std::sync::Arc<std::sync::RwLock<ChainHeads>>,
+    chain_head_updates: std::sync::Arc<task::Listeners<ChainHeads>>,
+    chain_heads_stale: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    transactions: std::sync::Arc<tx_status::TransactionTracker>,
+    policies: policy::PolicyEngine,
+    daily_spending: std::sync::Arc<policy::DailySpending>,
+    journal: Option<std::sync::Arc<TransactionJournal>>,
+    background_tasks: Vec<futures::future::AbortHandle>,
+    #[cfg(feature = "index-db")]
+    history_index: Option<HistoryIndex>,
     accounts: std::collections::HashMap<String, OwnedAccount>,
+    /// Held from signing until the node received the extrinsic, so that concurrent submissions
+    /// from an account don't sign with the same nonce.
+    sender_locks: std::collections::HashMap<String, futures::lock::Mutex<()>>,
 }
 
 impl WalletInstance {
+    pub const MAX_CUSTODY: usize = u8::MAX as usize;
     #[cfg(not(feature = "wasm32"))]
+    #[deprecated(note = "use the `rpc_urls` of `NetworkConfig::mainnet()`")]
     pub const BOOTNODE_URL: &'static str = "wss://boot.nagara.network:443";
     #[cfg(feature = "wasm32")]
+    #[deprecated(note = "use the `rpc_urls` of `NetworkConfig::mainnet()`")]
     pub const BOOTNODE_URL: &'static str = "https://boot.nagara.network:443";
+    #[deprecated(note = "use `NetworkConfig::block_url`")]
     pub const BASE_BLOCK_URL: &'static str =
         "https://nagara.network/?rpc=wss%3A%2F%2Fboot.nagara.network#/explorer/query";
-    pub const MAX_CUSTODY: usize = u8::MAX as usize;
 
     pub async fn create_with_default_url() -> crate::Result<Self> {
-        Self::create_with_url(Self::BOOTNODE_URL).await
+        Self::create_with_network(NetworkConfig::mainnet()).await
     }
 
     pub async fn create_with_url<U: core::convert::AsRef<str>>(url: U) -> crate::Result<Self> {
-        let client = subxt::OnlineClient::<subxt::PolkadotConfig>::from_url(url).await?;
+        Self::create_with_urls(&[url]).await
+    }
+
+    pub async fn create_with_urls<U: core::convert::AsRef<str>>(urls: &[U]) -> crate::Result<Self> {
+        Self::create_with_network(NetworkConfig::mainnet().with_rpc_urls(urls)).await
+    }
+
+    /// Connect to the first healthy endpoint of the network, the others are used as failover.
+    ///
+    /// Background tasks are spawned on the current Tokio runtime, or on the browser event loop
+    /// with the `wasm32` feature, so this fails outside a runtime.
+    pub async fn create_with_network(network: NetworkConfig) -> crate::Result<Self> {
+        #[cfg(not(feature = "wasm32"))]
+        tokio::runtime::Handle::try_current().map_err(|err| {
+            crate::Error::OtherError(format!("Wallet must run inside a Tokio runtime: {err}"))
+        })?;
+
+        if network.rpc_urls.is_empty() {
+            return Err(crate::Error::InvalidArgument(format!(
+                "Network {} has no RPC URL",
+                network.name
+            )));
+        }
+
+        let rpc_client = rpc::FailoverRpcClient::connect(&network.rpc_urls).await?;
+        let client = subxt::OnlineClient::<subxt::PolkadotConfig>::from_rpc_client(
+            std::sync::Arc::new(rpc_client.clone()),
+        )
+        .await?;
+        let runtime_mode = std::sync::Arc::new(std::sync::RwLock::new(compat::verify_chain(
+            &client, &network,
+        )?));
+        let health_monitor = task::spawn(rpc_client.clone().monitor_health());
+        let runtime_upgrades = std::sync::Arc::new(task::Listeners::new());
+        let upgrade_watcher = task::spawn(upgrade::watch_runtime_upgrades(
+            client.clone(),
+            network.clone(),
+            runtime_mode.clone(),
+            runtime_upgrades.clone(),
+        ));
+        let chain_heads = std::sync::Arc::new(std::sync::RwLock::new(
+            head::fetch_chain_heads(&client).await?,
+        ));
+        let chain_head_updates = std::sync::Arc::new(task::Listeners::new());
+        let chain_heads_stale = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let head_tracker = task::spawn(head::track_chain_heads(
+            client.clone(),
+            chain_heads.clone(),
+            chain_head_updates.clone(),
+            chain_heads_stale.clone(),
+        ));
 
         Ok(Self {
             client,
+            rpc_client,
+            network,
+            runtime_mode,
+            runtime_upgrades,
+            chain_heads,
+            chain_head_updates,
+            chain_heads_stale,
+            transactions: std::sync::Arc::new(tx_status::TransactionTracker::new()),
+            policies: policy::PolicyEngine::default(),
+            daily_spending: std::sync::Arc::new(policy::DailySpending::default()),
+            journal: None,
+            background_tasks: vec![health_monitor, upgrade_watcher, head_tracker],
+            #[cfg(feature = "index-db")]
+            history_index: None,
             accounts: std::collections::HashMap::with_capacity(Self::MAX_CUSTODY),
+            sender_locks: std::collections::HashMap::with_capacity(Self::MAX_CUSTODY),
         })
     }
 
+    pub fn network(&self) -> &NetworkConfig {
+        &self.network
+    }
+
+    pub fn runtime_mode(&self) -> RuntimeMode {
+        *self.runtime_mode.read().unwrap()
+    }
+
+    /// Receive an event every time the wallet followed a runtime upgrade.
+    pub fn runtime_upgrades(&self) -> futures::channel::mpsc::UnboundedReceiver<RuntimeUpgrade> {
+        self.runtime_upgrades.subscribe()
+    }
+
+    fn compatible_runtime_mode(&self) -> crate::Result<RuntimeMode> {
+        compat::compatible_runtime_mode(&self.client, &self.runtime_mode)
+    }
+
+    pub fn current_endpoint(&self) -> String {
+        self.rpc_client.current_url()
+    }
+
     pub fn add_account(&mut self, secret_str: &str, use_schnorrkel: bool) -> crate::Result<String> {
         if self.accounts.len() >= Self::MAX_CUSTODY {
             return crate::Result::Err(Error::AccountFull);
@@ -147,31 +360,21 @@ impl WalletInstance {
         let account = OwnedAccount::new_from_str(secret_str, use_schnorrkel)?;
         let account_str = account.get_main_address();
         self.accounts.insert(account_str.clone(), account);
+        self.sender_locks.entry(account_str.clone()).or_default();
 
         Ok(account_str)
     }
 
-    pub async fn check_balance(&self, account_address: &str) -> crate::Result<u128> {
-        let account =
-            <subxt::utils::AccountId32 as core::str::FromStr>::from_str(account_address).unwrap();
-        let data_pointer = metadata::nagara::api::storage().system().account(account);
-        let maybe_account_info_exist = self
-            .client
-            .storage()
-            .at_latest()
-            .await?
-            .fetch(&data_pointer)
-            .await?;
+    /// Addresses of the accounts added to the wallet, sorted.
+    pub fn account_addresses(&self) -> Vec<String> {
+        let mut account_addresses = self.accounts.keys().cloned().collect::<Vec<_>>();
+        account_addresses.sort();
 
-        if let Some(account_info) = maybe_account_info_exist {
-            let free_amount = account_info.data.free;
-            let locked_amount = account_info.data.frozen;
-            let reserved_amount = account_info.data.reserved;
+        account_addresses
+    }
 
-            Ok(free_amount + locked_amount + reserved_amount)
-        } else {
-            Ok(0)
-        }
+    pub async fn check_balance(&self, account_address: &str) -> crate::Result<u128> {
+        Ok(self.balance_info(account_address).await?.total())
     }
 
     pub async fn transfer(
@@ -180,44 +383,140 @@ impl WalletInstance {
         recipient_address: &str,
         balance: u128,
     ) -> crate::Result<String> {
+        self.transfer_with_options(
+            sender_address,
+            recipient_address,
+            balance,
+            &TxOptions::default(),
+        )
+        .await
+    }
+
+    pub async fn transfer_with_options(
+        &self,
+        sender_address: &str,
+        recipient_address: &str,
+        balance: u128,
+        options: &TxOptions,
+    ) -> crate::Result<String> {
+        let recipient_account = compat::parse_account(recipient_address)?;
+        let payouts = [Payout {
+            recipient_address: recipient_address.to_string(),
+            amount: balance,
+        }];
+        let progress = if self.compatible_runtime_mode()? == RuntimeMode::Dynamic {
+            let tx_payload = compat::dynamic_transfer_keep_alive(recipient_account, balance);
+
+            self.submit_with_options(sender_address, &payouts, &tx_payload, options)
+                .await?
+        } else {
+            let tx_payload = metadata::nagara::api::tx()
+                .balances()
+                .transfer_keep_alive(recipient_account.into(), balance);
+
+            self.submit_with_options(sender_address, &payouts, &tx_payload, options)
+                .await?
+        };
+        let block_hash = progress.wait_for_in_block().await?.block_hash();
+
+        Ok(self.block_url(block_hash))
+    }
+
+    pub fn block_url(&self, block_hash: BlockHash) -> String {
+        self.network.block_url(block_hash)
+    }
+
+    async fn submit_with_options<Call: subxt::tx::TxPayload>(
+        &self,
+        sender_address: &str,
+        payouts: &[Payout],
+        call: &Call,
+        options: &TxOptions,
+    ) -> crate::Result<tx_options::TxProgress> {
+        let (_, progress) = self
+            .broadcast(None, sender_address, payouts, call, options)
+            .await?;
+
+        Ok(progress)
+    }
+
+    fn sender_lock(&self, sender_address: &str) -> crate::Result<&futures::lock::Mutex<()>> {
+        self.sender_locks
+            .get(sender_address)
+            .ok_or(Error::AccountNotFound)
+    }
+
+    async fn sign_with_options<Call: subxt::tx::TxPayload>(
+        &self,
+        sender_address: &str,
+        call: &Call,
+        options: &TxOptions,
+    ) -> crate::Result<tx_options::SignedExtrinsic> {
+        let (extrinsic, _) = self.sign_authorized(sender_address, call, options).await?;
+
+        Ok(extrinsic)
+    }
+
+    /// Sign after checking the spending policy of the sender, the amount reserved against its
+    /// daily limit is given back when the reservation is dropped without being committed.
+    async fn sign_authorized<Call: subxt::tx::TxPayload>(
+        &self,
+        sender_address: &str,
+        call: &Call,
+        options: &TxOptions,
+    ) -> crate::Result<(
+        tx_options::SignedExtrinsic,
+        Option<policy::SpendingReservation>,
+    )> {
         let sender_account = self
             .accounts
             .get(sender_address)
             .ok_or(Error::AccountNotFound)?;
-        let recipient_account =
-            <subxt::utils::AccountId32 as core::str::FromStr>::from_str(recipient_address).unwrap();
-        let tx_payload = metadata::nagara::api::tx()
-            .balances()
-            .transfer_keep_alive(recipient_account.into(), balance);
-        let block_hash = self
-            .client
-            .tx()
-            .sign_and_submit_then_watch_default(&tx_payload, sender_account)
-            .await?
-            .wait_for_in_block()
-            .await?
-            .block_hash();
-        let block_hash_hex = hex::encode(block_hash);
-        let block_info = format!("{}/0x{block_hash_hex}", Self::BASE_BLOCK_URL);
-
-        Ok(block_info)
+        let reservation = self
+            .authorize_call(
+                &<OwnedAccount as subxt::tx::Signer<subxt::PolkadotConfig>>::account_id(
+                    sender_account,
+                ),
+                call,
+                options.get_tip(),
+            )
+            .await?;
+        let params = options
+            .build_params(&self.client, &self.chain_heads().best)
+            .await?;
+        let tx_client = self.client.tx();
+        let extrinsic = match options.get_nonce() {
+            Some(nonce) => {
+                tx_client.create_signed_with_nonce(call, sender_account, nonce, params)?
+            }
+            None => {
+                tx_client
+                    .create_signed(call, sender_account, params)
+                    .await?
+            }
+        };
+
+        Ok((extrinsic, reservation))
     }
 
+    /// Number of the latest finalized or best block, read from [`Self::chain_heads`].
     pub async fn latest_block(&self, finalized: bool) -> crate::Result<u32> {
-        let blocks_client = self.client.blocks();
-        let result = if finalized {
-            blocks_client.subscribe_finalized().await?.next().await
-        } else {
-            blocks_client.subscribe_best().await?.next().await
-        };
+        let chain_heads = self.chain_heads();
 
-        match result {
-            Some(block_subscribe_result) => {
-                let block_info = block_subscribe_result?;
+        if finalized {
+            Ok(chain_heads.finalized_number())
+        } else {
+            Ok(chain_heads.best_number())
+        }
+    }
+}
 
-                Ok(block_info.number())
-            }
-            None => Ok(0),
+impl Drop for WalletInstance {
+    fn drop(&mut self) {
+        for background_task in &self.background_tasks {
+            background_task.abort();
         }
+
+        self.transactions.abort();
     }
 }
@@ -0,0 +1,171 @@
+/// Everything the wallet needs to know about the network it talks to.
+#[derive(Clone, core::fmt::Debug)]
+pub struct NetworkConfig {
+    pub name: String,
+    pub rpc_urls: Vec<String>,
+    /// Explorer URL of a block, `{block_hash}` is replaced with the `0x` prefixed hash and
+    /// `{rpc_url}` with the percent-encoded first RPC URL.
+    pub explorer_block_url: String,
+    pub ss58_prefix: u16,
+    pub token_symbol: String,
+    pub token_decimals: u8,
+    /// Checked against the node on connect when set, the genesis hash of the node is logged
+    /// otherwise.
+    pub genesis_hash: Option<crate::BlockHash>,
+    /// Checked against the node on connect when set.
+    pub spec_version: Option<u32>,
+    /// Checked against the node on connect when set.
+    pub transaction_version: Option<u32>,
+    /// Talk to a runtime not matching the built-in metadata through live metadata instead of
+    /// failing with `IncompatibleRuntime`.
+    pub allow_dynamic_fallback: bool,
+}
+
+impl NetworkConfig {
+    pub const BLOCK_HASH_PLACEHOLDER: &'static str = "{block_hash}";
+    pub const RPC_URL_PLACEHOLDER: &'static str = "{rpc_url}";
+    const EXPLORER_BLOCK_URL: &'static str =
+        "https://nagara.network/?rpc={rpc_url}#/explorer/query/{block_hash}";
+    pub const LOCAL_SS58_PREFIX: u16 = 42;
+    pub const LOCAL_TOKEN_DECIMALS: u8 = 12;
+
+    pub fn mainnet() -> Self {
+        let token = ss58_registry::Token::from(ss58_registry::TokenRegistry::Ngr);
+
+        Self {
+            name: "mainnet".to_string(),
+            #[cfg(not(feature = "wasm32"))]
+            rpc_urls: vec!["wss://boot.nagara.network:443".to_string()],
+            #[cfg(feature = "wasm32")]
+            rpc_urls: vec!["https://boot.nagara.network:443".to_string()],
+            explorer_block_url: Self::EXPLORER_BLOCK_URL.to_string(),
+            ss58_prefix: ss58_registry::Ss58AddressFormat::from(
+                ss58_registry::Ss58AddressFormatRegistry::NagaraAccount,
+            )
+            .prefix(),
+            token_symbol: token.name.to_string(),
+            token_decimals: token.decimals,
+            genesis_hash: None,
+            spec_version: None,
+            transaction_version: None,
+            allow_dynamic_fallback: false,
+        }
+    }
+
+    /// Token and address format of mainnet, without RPC URLs as no public testnet endpoint is
+    /// published, set them with [`Self::with_rpc_urls`].
+    pub fn testnet() -> Self {
+        let mainnet = Self::mainnet();
+
+        Self {
+            name: "testnet".to_string(),
+            rpc_urls: Vec::new(),
+            ..mainnet
+        }
+    }
+
+    pub fn local() -> Self {
+        Self {
+            name: "local".to_string(),
+            #[cfg(not(feature = "wasm32"))]
+            rpc_urls: vec!["ws://127.0.0.1:9944".to_string()],
+            #[cfg(feature = "wasm32")]
+            rpc_urls: vec!["http://127.0.0.1:9944".to_string()],
+            explorer_block_url: Self::EXPLORER_BLOCK_URL.to_string(),
+            ss58_prefix: Self::LOCAL_SS58_PREFIX,
+            token_symbol: "UNIT".to_string(),
+            token_decimals: Self::LOCAL_TOKEN_DECIMALS,
+            genesis_hash: None,
+            spec_version: None,
+            transaction_version: None,
+            allow_dynamic_fallback: false,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mainnet" => Some(Self::mainnet()),
+            "testnet" => Some(Self::testnet()),
+            "local" => Some(Self::local()),
+            _ => None,
+        }
+    }
+
+    pub fn with_rpc_urls<U: core::convert::AsRef<str>>(mut self, rpc_urls: &[U]) -> Self {
+        self.rpc_urls = rpc_urls
+            .iter()
+            .map(|rpc_url| rpc_url.as_ref().to_string())
+            .collect();
+
+        self
+    }
+
+    pub fn with_genesis_hash(mut self, genesis_hash: crate::BlockHash) -> Self {
+        self.genesis_hash = Some(genesis_hash);
+
+        self
+    }
+
+    pub fn with_runtime_versions(mut self, spec_version: u32, transaction_version: u32) -> Self {
+        self.spec_version = Some(spec_version);
+        self.transaction_version = Some(transaction_version);
+
+        self
+    }
+
+    pub fn with_dynamic_fallback(mut self, allow_dynamic_fallback: bool) -> Self {
+        self.allow_dynamic_fallback = allow_dynamic_fallback;
+
+        self
+    }
+
+    /// SS58 address of `account_id` in the format of the network.
+    pub fn encode_address(&self, account_id: &subxt::utils::AccountId32) -> String {
+        encode_ss58(self.ss58_prefix, account_id)
+    }
+
+    pub fn block_url(&self, block_hash: crate::BlockHash) -> String {
+        let block_hash_hex = format!("0x{}", hex::encode(block_hash));
+        let rpc_url = self.rpc_urls.first().map_or_else(String::new, |rpc_url| {
+            rpc_url
+                .bytes()
+                .map(|byte| match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                        char::from(byte).to_string()
+                    }
+                    _ => format!("%{byte:02X}"),
+                })
+                .collect()
+        });
+
+        self.explorer_block_url
+            .replace(Self::BLOCK_HASH_PLACEHOLDER, &block_hash_hex)
+            .replace(Self::RPC_URL_PLACEHOLDER, &rpc_url)
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+/// Encode `account_id` as an SS58 address with `ss58_prefix`, the checksum being the first two
+/// bytes of the BLAKE2b-512 hash of `SS58PRE`, the prefix and the account id.
+pub(crate) fn encode_ss58(ss58_prefix: u16, account_id: &subxt::utils::AccountId32) -> String {
+    let ss58_prefix = ss58_prefix & 0b0011_1111_1111_1111;
+    let mut address = match ss58_prefix {
+        0..=63 => vec![ss58_prefix as u8],
+        _ => vec![
+            (((ss58_prefix & 0b0000_0000_1111_1100) as u8) >> 2) | 0b0100_0000,
+            ((ss58_prefix >> 8) as u8) | (((ss58_prefix & 0b0000_0000_0000_0011) as u8) << 6),
+        ],
+    };
+    address.extend(account_id.0);
+    let hasher = <blake2::Blake2b512 as blake2::Digest>::new();
+    let hasher = blake2::Digest::chain_update(hasher, b"SS58PRE");
+    let checksum = blake2::Digest::finalize(blake2::Digest::chain_update(hasher, &address));
+    address.extend(&checksum[..2]);
+
+    base58::ToBase58::to_base58(address.as_slice())
+}
@@ -0,0 +1,153 @@
+/// One transfer of a batch payout, the amount is in the smallest unit of the native token.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Payout {
+    pub recipient_address: String,
+    pub amount: u128,
+}
+
+impl crate::WalletInstance {
+    pub(crate) fn transfer_call(
+        &self,
+        recipient_address: &str,
+        balance: u128,
+    ) -> crate::Result<crate::extrinsic::RawCall> {
+        let recipient_account = crate::compat::parse_account(recipient_address)?;
+        let metadata = self.client.metadata();
+        let call_data = if self.compatible_runtime_mode()? == crate::RuntimeMode::Dynamic {
+            let tx_payload = crate::compat::dynamic_transfer_keep_alive(recipient_account, balance);
+
+            subxt::tx::TxPayload::encode_call_data(&tx_payload, &metadata)?
+        } else {
+            let tx_payload = crate::metadata::nagara::api::tx()
+                .balances()
+                .transfer_keep_alive(recipient_account.into(), balance);
+
+            subxt::tx::TxPayload::encode_call_data(&tx_payload, &metadata)?
+        };
+
+        Ok(crate::extrinsic::RawCall(call_data))
+    }
+
+    pub(crate) fn payouts_call(
+        &self,
+        payouts: &[Payout],
+    ) -> crate::Result<crate::extrinsic::RawCall> {
+        if payouts.is_empty() {
+            return Err(crate::Error::InvalidArgument(
+                "Payout has no recipient".to_string(),
+            ));
+        }
+
+        let metadata = self.client.metadata();
+        let call_data = if self.compatible_runtime_mode()? == crate::RuntimeMode::Dynamic {
+            let calls = payouts
+                .iter()
+                .map(|payout| {
+                    let recipient_account =
+                        crate::compat::parse_account(&payout.recipient_address)?;
+
+                    Ok(crate::compat::dynamic_transfer_keep_alive_call(
+                        recipient_account,
+                        payout.amount,
+                    ))
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            let tx_payload = crate::compat::dynamic_batch_all(calls);
+
+            subxt::tx::TxPayload::encode_call_data(&tx_payload, &metadata)?
+        } else {
+            let calls = payouts
+                .iter()
+                .map(|payout| {
+                    let recipient_account =
+                        crate::compat::parse_account(&payout.recipient_address)?;
+
+                    Ok(crate::metadata::nagara::api::runtime_types::nagara_runtime::RuntimeCall::Balances(
+                        crate::metadata::nagara::api::runtime_types::pallet_balances::pallet::Call::transfer_keep_alive {
+                            dest: recipient_account.into(),
+                            value: payout.amount,
+                        },
+                    ))
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            let tx_payload = crate::metadata::nagara::api::tx()
+                .utility()
+                .batch_all(calls);
+
+            subxt::tx::TxPayload::encode_call_data(&tx_payload, &metadata)?
+        };
+
+        Ok(crate::extrinsic::RawCall(call_data))
+    }
+
+    /// Submit a transfer without waiting for its inclusion, follow it with
+    /// [`Self::transaction_status`].
+    pub async fn submit_transfer(
+        &self,
+        sender_address: &str,
+        recipient_address: &str,
+        balance: u128,
+        options: &crate::TxOptions,
+    ) -> crate::Result<crate::ExtrinsicHash> {
+        let call = self.transfer_call(recipient_address, balance)?;
+        let payouts = [Payout {
+            recipient_address: recipient_address.to_string(),
+            amount: balance,
+        }];
+
+        self.submit_tracked(sender_address, &payouts, &call, options)
+            .await
+    }
+
+    /// Pay every recipient in one `utility.batch_all` extrinsic, either all of the transfers
+    /// succeed or none of them.
+    pub async fn submit_payouts(
+        &self,
+        sender_address: &str,
+        payouts: &[Payout],
+        options: &crate::TxOptions,
+    ) -> crate::Result<crate::ExtrinsicHash> {
+        let call = self.payouts_call(payouts)?;
+
+        self.submit_tracked(sender_address, payouts, &call, options)
+            .await
+    }
+
+    /// Fee the transfer would pay without its tip, in the smallest unit of the native token.
+    pub async fn estimate_transfer_fee(
+        &self,
+        sender_address: &str,
+        recipient_address: &str,
+        balance: u128,
+        options: &crate::TxOptions,
+    ) -> crate::Result<u128> {
+        let call = self.transfer_call(recipient_address, balance)?;
+
+        self.estimate_fee(sender_address, &call, options).await
+    }
+
+    /// Fee the payout would pay without its tip, see [`Self::submit_payouts`].
+    pub async fn estimate_payouts_fee(
+        &self,
+        sender_address: &str,
+        payouts: &[Payout],
+        options: &crate::TxOptions,
+    ) -> crate::Result<u128> {
+        let call = self.payouts_call(payouts)?;
+
+        self.estimate_fee(sender_address, &call, options).await
+    }
+
+    async fn estimate_fee(
+        &self,
+        sender_address: &str,
+        call: &crate::extrinsic::RawCall,
+        options: &crate::TxOptions,
+    ) -> crate::Result<u128> {
+        let extrinsic = self
+            .sign_with_options(sender_address, call, options)
+            .await?;
+
+        Ok(extrinsic.partial_fee_estimate().await?)
+    }
+}
@@ -0,0 +1,173 @@
+#[derive(Clone, core::fmt::Debug)]
+pub struct PendingTransaction {
+    pub extrinsic_hash: crate::ExtrinsicHash,
+    pub sender_address: String,
+    pub nonce: u64,
+    pub tip: u128,
+    pub era: crate::Era,
+    pub(crate) call_data: Vec<u8>,
+}
+
+#[derive(Clone, core::fmt::Debug)]
+pub enum ReplacementOutcome {
+    /// The replacement was included, the original transaction is gone from the pool.
+    Replacement {
+        extrinsic_hash: crate::ExtrinsicHash,
+        block_hash: crate::BlockHash,
+    },
+    /// The original transaction was included before the replacement could take its place.
+    Original {
+        extrinsic_hash: crate::ExtrinsicHash,
+    },
+}
+
+impl crate::WalletInstance {
+    pub async fn pending_transactions(
+        &self,
+        sender_address: &str,
+    ) -> crate::Result<Vec<PendingTransaction>> {
+        let sender_account = crate::compat::parse_account(sender_address)?;
+        let pending_extrinsics: Vec<subxt::rpc::types::Bytes> = self
+            .client
+            .rpc()
+            .request("author_pendingExtrinsics", subxt::rpc::rpc_params![])
+            .await?;
+        let mut pending_transactions = Vec::new();
+
+        for pending_extrinsic in pending_extrinsics {
+            let Ok(envelope) = crate::extrinsic::ExtrinsicEnvelope::decode(&pending_extrinsic.0)
+            else {
+                continue;
+            };
+            let Some(signature) = envelope.signature else {
+                continue;
+            };
+
+            if signature.signer != sender_account {
+                continue;
+            }
+
+            pending_transactions.push(PendingTransaction {
+                extrinsic_hash: envelope.hash,
+                sender_address: sender_address.to_string(),
+                nonce: signature.nonce,
+                tip: signature.tip,
+                era: signature.era,
+                call_data: envelope.call_data,
+            });
+        }
+
+        pending_transactions.sort_by_key(|pending_transaction| pending_transaction.nonce);
+
+        Ok(pending_transactions)
+    }
+
+    /// Sign the pending call of `sender_address` at `nonce` again with the tip of `options`.
+    ///
+    /// The pool only accepts the replacement when its tip is higher than the original one.
+    pub async fn replace_transaction(
+        &self,
+        sender_address: &str,
+        nonce: u64,
+        options: &crate::TxOptions,
+    ) -> crate::Result<ReplacementOutcome> {
+        let original = self.find_pending(sender_address, nonce).await?;
+        let call = crate::extrinsic::RawCall(original.call_data.clone());
+
+        self.submit_replacement(sender_address, &original, &call, options)
+            .await
+    }
+
+    /// Replace the pending call of `sender_address` at `nonce` with an empty `system.remark`.
+    pub async fn cancel_transaction(
+        &self,
+        sender_address: &str,
+        nonce: u64,
+        options: &crate::TxOptions,
+    ) -> crate::Result<ReplacementOutcome> {
+        let original = self.find_pending(sender_address, nonce).await?;
+
+        if self.compatible_runtime_mode()? == crate::RuntimeMode::Dynamic {
+            let call = crate::compat::dynamic_remark(Vec::new());
+
+            return self
+                .submit_replacement(sender_address, &original, &call, options)
+                .await;
+        }
+
+        let call = crate::metadata::nagara::api::tx()
+            .system()
+            .remark(Vec::new());
+
+        self.submit_replacement(sender_address, &original, &call, options)
+            .await
+    }
+
+    async fn find_pending(
+        &self,
+        sender_address: &str,
+        nonce: u64,
+    ) -> crate::Result<PendingTransaction> {
+        self.pending_transactions(sender_address)
+            .await?
+            .into_iter()
+            .find(|pending_transaction| pending_transaction.nonce == nonce)
+            .ok_or(crate::Error::TransactionNotPending)
+    }
+
+    async fn submit_replacement<Call: subxt::tx::TxPayload>(
+        &self,
+        sender_address: &str,
+        original: &PendingTransaction,
+        call: &Call,
+        options: &crate::TxOptions,
+    ) -> crate::Result<ReplacementOutcome> {
+        if options.get_tip() <= original.tip {
+            return Err(crate::Error::ReplacementUnderpriced);
+        }
+
+        let sender_account = crate::compat::parse_account(sender_address)?;
+        let options = options.clone().nonce(original.nonce);
+        // The original may be included before the replacement reaches the pool, which then
+        // rejects the replacement as stale.
+        let rejection = match self
+            .submit_with_options(sender_address, &[], call, &options)
+            .await
+        {
+            Ok(mut progress) => {
+                while let Some(status) = futures::StreamExt::next(&mut progress).await {
+                    match status? {
+                        subxt::tx::TxStatus::InBlock(in_block)
+                        | subxt::tx::TxStatus::Finalized(in_block) => {
+                            return Ok(ReplacementOutcome::Replacement {
+                                extrinsic_hash: in_block.extrinsic_hash(),
+                                block_hash: in_block.block_hash(),
+                            });
+                        }
+                        subxt::tx::TxStatus::Usurped(_)
+                        | subxt::tx::TxStatus::Dropped
+                        | subxt::tx::TxStatus::Invalid => break,
+                        _ => continue,
+                    }
+                }
+
+                crate::Error::ReplacementDropped
+            }
+            Err(
+                err @ (crate::Error::InvalidTransaction(_)
+                | crate::Error::SubxtError(subxt::Error::Rpc(_))),
+            ) => err,
+            Err(err) => return Err(err),
+        };
+
+        let next_nonce = self.client.tx().account_nonce(&sender_account).await?;
+
+        if next_nonce > original.nonce {
+            Ok(ReplacementOutcome::Original {
+                extrinsic_hash: original.extrinsic_hash,
+            })
+        } else {
+            Err(rejection)
+        }
+    }
+}
@@ -0,0 +1,711 @@
+/// Why a spending policy refused to sign a transaction, amounts are in the smallest unit of the
+/// native token.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PolicyViolation {
+    #[error("Amount {amount} exceeds the limit of {limit} per transaction")]
+    TransactionLimit { amount: u128, limit: u128 },
+    #[error("Amount {amount} exceeds the daily limit of {limit}, {spent} already spent today")]
+    DailyLimit {
+        amount: u128,
+        spent: u128,
+        limit: u128,
+    },
+    #[error("Recipient {0} is not allowed")]
+    RecipientNotAllowed(String),
+    #[error("Recipient {0} is denied")]
+    RecipientDenied(String),
+    #[error("Call {pallet}::{call} is not allowed")]
+    CallNotAllowed { pallet: String, call: String },
+    #[error("Amount moved by {pallet}::{call} can't be checked against the limits")]
+    UncheckedAmount { pallet: String, call: String },
+}
+
+/// Limits on what an account of the wallet may sign.
+///
+/// Amounts are in the smallest unit of the native token and count the tip, fees excluded. Calls
+/// nested in batches or sudo are checked like the outer one. With limits, calls which
+/// may move native funds other than the transfers of Balances are refused.
+#[derive(Clone, core::fmt::Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SpendingPolicy {
+    pub max_per_transaction: Option<u128>,
+    /// Limit of a UTC day, following the timestamp of the best block. Spending only outlives the
+    /// wallet with [`crate::WalletInstance::set_spending_file`].
+    pub max_per_day: Option<u128>,
+    /// SS58 addresses transfers may go to, any when unset.
+    pub allowed_recipients: Option<Vec<String>>,
+    /// SS58 addresses transfers may never go to.
+    pub denied_recipients: Vec<String>,
+    /// `Pallet` or `Pallet::call` names the account may sign, any when unset. A payout needs
+    /// `Utility::batch_all` besides the transfer.
+    pub allowed_calls: Option<Vec<String>>,
+}
+
+/// Spending policies of the wallet, loaded from a JSON file with [`Self::load`].
+#[derive(Clone, core::fmt::Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SpendingPolicies {
+    /// Policy of the accounts without their own one, they are unrestricted when unset.
+    pub default: Option<SpendingPolicy>,
+    /// Policies keyed by SS58 address.
+    pub accounts: std::collections::HashMap<String, SpendingPolicy>,
+}
+
+impl SpendingPolicies {
+    pub fn load<P: core::convert::AsRef<std::path::Path>>(policy_path: P) -> crate::Result<Self> {
+        let policies =
+            std::fs::read(policy_path).map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        serde_json::from_slice(&policies)
+            .map_err(|err| crate::Error::InvalidArgument(format!("Invalid policy file: {err}")))
+    }
+}
+
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Calls moving no native funds by themselves besides fees and deposits, the calls nested in them
+/// are checked on their own. Under limits, the calls outside Balances not listed here are refused
+/// since their amounts aren't counted.
+const CALLS_WITHOUT_AMOUNT: &[&str] = &[
+    "System::remark",
+    "System::remark_with_event",
+    "Utility::batch",
+    "Utility::batch_all",
+    "Utility::force_batch",
+    "Sudo::sudo",
+    "Sudo::sudo_unchecked_weight",
+];
+
+/// Whether one of the `Pallet` or `Pallet::call` names is the call.
+fn names_call<S: core::convert::AsRef<str>>(names: &[S], pallet: &str, call: &str) -> bool {
+    names
+        .iter()
+        .map(S::as_ref)
+        .any(|name| match name.split_once("::") {
+            Some((name_pallet, name_call)) => {
+                crate::dynamic::same_name(pallet, name_pallet)
+                    && crate::dynamic::same_name(call, name_call)
+            }
+            None => crate::dynamic::same_name(pallet, name),
+        })
+}
+
+/// A [`SpendingPolicy`] with its addresses parsed, so that any SS58 format of an address matches.
+#[derive(Clone, core::fmt::Debug)]
+struct ParsedPolicy {
+    policy: SpendingPolicy,
+    allowed_recipients: Option<std::collections::HashSet<subxt::utils::AccountId32>>,
+    denied_recipients: std::collections::HashSet<subxt::utils::AccountId32>,
+}
+
+impl ParsedPolicy {
+    fn parse(policy: SpendingPolicy) -> crate::Result<Self> {
+        let parse_accounts = |addresses: &[String]| {
+            addresses
+                .iter()
+                .map(|address| crate::compat::parse_account(address))
+                .collect::<crate::Result<std::collections::HashSet<_>>>()
+        };
+        let allowed_recipients = match &policy.allowed_recipients {
+            Some(addresses) => Some(parse_accounts(addresses)?),
+            None => None,
+        };
+        let denied_recipients = parse_accounts(&policy.denied_recipients)?;
+
+        Ok(Self {
+            policy,
+            allowed_recipients,
+            denied_recipients,
+        })
+    }
+
+    fn allows_call(&self, pallet: &str, call: &str) -> bool {
+        let Some(allowed_calls) = &self.policy.allowed_calls else {
+            return true;
+        };
+
+        names_call(allowed_calls, pallet, call)
+    }
+
+    fn check_recipient(&self, dest: &serde_json::Value) -> Result<(), PolicyViolation> {
+        if self.allowed_recipients.is_none() && self.denied_recipients.is_empty() {
+            return Ok(());
+        }
+
+        let Some(recipient) = recipient_account(dest) else {
+            return Err(PolicyViolation::RecipientNotAllowed(dest.to_string()));
+        };
+
+        if self.denied_recipients.contains(&recipient) {
+            return Err(PolicyViolation::RecipientDenied(recipient.to_string()));
+        }
+
+        match &self.allowed_recipients {
+            Some(allowed_recipients) if !allowed_recipients.contains(&recipient) => {
+                Err(PolicyViolation::RecipientNotAllowed(recipient.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn has_limits(&self) -> bool {
+        self.policy.max_per_transaction.is_some() || self.policy.max_per_day.is_some()
+    }
+
+    /// Check the call with the calls nested in it, adding the native amounts they move to
+    /// `amount`.
+    fn check_call(&self, call: &crate::CallInfo, amount: &mut u128) -> Result<(), PolicyViolation> {
+        let unchecked_amount = || PolicyViolation::UncheckedAmount {
+            pallet: call.pallet.clone(),
+            call: call.call.clone(),
+        };
+
+        if !self.allows_call(&call.pallet, &call.call) {
+            return Err(PolicyViolation::CallNotAllowed {
+                pallet: call.pallet.clone(),
+                call: call.call.clone(),
+            });
+        }
+
+        if crate::dynamic::same_name(&call.pallet, "Balances") {
+            match call.call.as_str() {
+                "transfer" | "transfer_allow_death" | "transfer_keep_alive" | "force_transfer" => {
+                    self.check_recipient(&call.args["dest"])?;
+                    let value = json_amount(&call.args["value"]).ok_or_else(unchecked_amount)?;
+                    *amount = amount.saturating_add(value);
+                }
+                "transfer_all" => {
+                    self.check_recipient(&call.args["dest"])?;
+
+                    if self.has_limits() {
+                        return Err(unchecked_amount());
+                    }
+                }
+                _ => {}
+            }
+        } else if crate::dynamic::same_name(&call.pallet, "Assets") {
+            match call.call.as_str() {
+                "transfer" | "transfer_keep_alive" => {
+                    self.check_recipient(&call.args["target"])?;
+                }
+                "force_transfer" => self.check_recipient(&call.args["dest"])?,
+                "transfer_approved" => self.check_recipient(&call.args["destination"])?,
+                _ => {}
+            }
+        } else if self.has_limits() && !names_call(CALLS_WITHOUT_AMOUNT, &call.pallet, &call.call) {
+            // E.g. `Vesting::vested_transfer` or `Staking::bond` move funds the limits can't see.
+            return Err(unchecked_amount());
+        }
+
+        for nested_call in &call.nested_calls {
+            self.check_call(nested_call, amount)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Account of a `MultiAddress::Id` or plain account argument, as decoded to JSON.
+fn recipient_account(dest: &serde_json::Value) -> Option<subxt::utils::AccountId32> {
+    let address = match dest {
+        serde_json::Value::String(address) => address,
+        serde_json::Value::Object(dest) => dest.get("Id")?.as_str()?,
+        _ => return None,
+    };
+
+    <subxt::utils::AccountId32 as core::str::FromStr>::from_str(address).ok()
+}
+
+fn json_amount(value: &serde_json::Value) -> Option<u128> {
+    match value {
+        serde_json::Value::Number(value) => value.as_u64().map(u128::from),
+        serde_json::Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+#[derive(Clone, core::fmt::Debug, Default)]
+pub(crate) struct PolicyEngine {
+    default: Option<ParsedPolicy>,
+    accounts: std::collections::HashMap<subxt::utils::AccountId32, ParsedPolicy>,
+}
+
+impl PolicyEngine {
+    fn parse(policies: SpendingPolicies) -> crate::Result<Self> {
+        let default = match policies.default {
+            Some(policy) => Some(ParsedPolicy::parse(policy)?),
+            None => None,
+        };
+        let accounts = policies
+            .accounts
+            .into_iter()
+            .map(|(address, policy)| {
+                Ok((
+                    crate::compat::parse_account(&address)?,
+                    ParsedPolicy::parse(policy)?,
+                ))
+            })
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Self { default, accounts })
+    }
+
+    fn policy(&self, account: &subxt::utils::AccountId32) -> Option<&ParsedPolicy> {
+        self.accounts.get(account).or(self.default.as_ref())
+    }
+}
+
+/// Amount an account spent during a UTC day, as saved in the spending file.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DaySpending {
+    account: subxt::utils::AccountId32,
+    day: u64,
+    spent: u128,
+}
+
+/// Amounts spent per account during the current UTC day, reserved while the transaction is signed
+/// so that concurrent transactions can't go over the daily limit together.
+///
+/// With a spending file, the amounts are saved as soon as they are reserved or released, so that
+/// a crash can only overestimate them.
+#[derive(core::fmt::Debug, Default)]
+pub(crate) struct DailySpending {
+    spent: std::sync::Mutex<std::collections::HashMap<subxt::utils::AccountId32, (u64, u128)>>,
+    spending_path: Option<std::path::PathBuf>,
+}
+
+impl DailySpending {
+    /// Read the spending file, a missing file gives no spending.
+    pub(crate) fn open(spending_path: std::path::PathBuf) -> crate::Result<Self> {
+        let spent = if spending_path.exists() {
+            let spending = std::fs::read(&spending_path)
+                .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+            serde_json::from_slice::<Vec<DaySpending>>(&spending)
+                .map_err(|err| crate::Error::OtherError(format!("Corrupted spending file: {err}")))?
+                .into_iter()
+                .map(|day_spending| (day_spending.account, (day_spending.day, day_spending.spent)))
+                .collect()
+        } else {
+            std::collections::HashMap::new()
+        };
+
+        Ok(Self {
+            spent: std::sync::Mutex::new(spent),
+            spending_path: Some(spending_path),
+        })
+    }
+
+    fn save(
+        &self,
+        spent: &std::collections::HashMap<subxt::utils::AccountId32, (u64, u128)>,
+    ) -> crate::Result<()> {
+        let Some(spending_path) = &self.spending_path else {
+            return Ok(());
+        };
+        let spending = spent
+            .iter()
+            .map(|(account, (day, spent))| DaySpending {
+                account: account.clone(),
+                day: *day,
+                spent: *spent,
+            })
+            .collect::<Vec<_>>();
+        let spending = serde_json::to_vec(&spending)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+        crate::journal::replace_file(spending_path, &spending)
+            .map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+
+    /// Count the amount against the daily limit, saved before the transaction can be broadcast.
+    fn reserve(
+        self: &std::sync::Arc<Self>,
+        account: subxt::utils::AccountId32,
+        day: u64,
+        amount: u128,
+        limit: u128,
+    ) -> crate::Result<SpendingReservation> {
+        let mut spent = self.spent.lock().unwrap();
+        let previous = spent.get(&account).copied();
+        let spent_today = match previous {
+            Some((spent_day, spent_amount)) if spent_day == day => spent_amount,
+            _ => 0,
+        };
+
+        if spent_today.saturating_add(amount) > limit {
+            return Err(crate::Error::PolicyViolation(PolicyViolation::DailyLimit {
+                amount,
+                spent: spent_today,
+                limit,
+            }));
+        }
+
+        spent.insert(account.clone(), (day, spent_today + amount));
+
+        if let Err(err) = self.save(&spent) {
+            match previous {
+                Some(previous) => spent.insert(account, previous),
+                None => spent.remove(&account),
+            };
+
+            return Err(err);
+        }
+
+        Ok(SpendingReservation {
+            spending: self.clone(),
+            account,
+            day,
+            amount,
+            committed: false,
+        })
+    }
+
+    fn release(&self, account: &subxt::utils::AccountId32, day: u64, amount: u128) {
+        let mut spent = self.spent.lock().unwrap();
+
+        if let Some((spent_day, spent_amount)) = spent.get_mut(account) {
+            if *spent_day == day {
+                *spent_amount = spent_amount.saturating_sub(amount);
+            }
+        }
+
+        // Keeping the reservation in the file only overestimates the spending.
+        if let Err(err) = self.save(&spent) {
+            nagara_logging::info!("Daily spending wasn't saved: {err}");
+        }
+    }
+}
+
+/// Amount counted against the daily limit, given back on drop unless the transaction was
+/// broadcast.
+pub(crate) struct SpendingReservation {
+    spending: std::sync::Arc<DailySpending>,
+    account: subxt::utils::AccountId32,
+    day: u64,
+    amount: u128,
+    committed: bool,
+}
+
+impl SpendingReservation {
+    pub(crate) fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for SpendingReservation {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.spending.release(&self.account, self.day, self.amount);
+        }
+    }
+}
+
+impl crate::WalletInstance {
+    /// Replace the spending policies checked before signing, the amounts already spent today are
+    /// kept.
+    pub fn set_spending_policies(&mut self, policies: SpendingPolicies) -> crate::Result<()> {
+        self.policies = PolicyEngine::parse(policies)?;
+
+        Ok(())
+    }
+
+    /// Keep the amounts spent today in this file, so that daily limits hold across runs of the
+    /// wallet. Replaces the amounts spent so far by the ones of the file.
+    pub fn set_spending_file<P: core::convert::Into<std::path::PathBuf>>(
+        &mut self,
+        spending_path: P,
+    ) -> crate::Result<()> {
+        self.daily_spending = std::sync::Arc::new(DailySpending::open(spending_path.into())?);
+
+        Ok(())
+    }
+
+    /// Check the call against the policy of the sender, reserving its amount when the policy has a
+    /// daily limit.
+    pub(crate) async fn authorize_call<Call: subxt::tx::TxPayload>(
+        &self,
+        sender_account: &subxt::utils::AccountId32,
+        call: &Call,
+        tip: u128,
+    ) -> crate::Result<Option<SpendingReservation>> {
+        let Some(policy) = self.policies.policy(sender_account) else {
+            return Ok(None);
+        };
+        let call_data = subxt::tx::TxPayload::encode_call_data(call, &self.client.metadata())?;
+        let call = self.decode_call(&call_data)?;
+        let mut amount = tip;
+        policy
+            .check_call(&call, &mut amount)
+            .map_err(crate::Error::PolicyViolation)?;
+
+        if let Some(limit) = policy.policy.max_per_transaction {
+            if amount > limit {
+                return Err(crate::Error::PolicyViolation(
+                    PolicyViolation::TransactionLimit { amount, limit },
+                ));
+            }
+        }
+
+        let Some(limit) = policy.policy.max_per_day else {
+            return Ok(None);
+        };
+        let timestamp = crate::historical::fetch_timestamp(
+            &self.client,
+            self.compatible_runtime_mode()?,
+            self.chain_heads().best_hash,
+        )
+        .await?;
+        let reservation = self.daily_spending.reserve(
+            sender_account.clone(),
+            timestamp / MILLIS_PER_DAY,
+            amount,
+            limit,
+        )?;
+
+        Ok(Some(reservation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    fn call(
+        pallet: &str,
+        call: &str,
+        args: serde_json::Value,
+        nested_calls: Vec<crate::CallInfo>,
+    ) -> crate::CallInfo {
+        crate::CallInfo {
+            pallet: pallet.to_string(),
+            call: call.to_string(),
+            args,
+            nested_calls,
+        }
+    }
+
+    fn transfer(dest: &str, value: serde_json::Value) -> crate::CallInfo {
+        call(
+            "Balances",
+            "transfer_keep_alive",
+            serde_json::json!({ "dest": { "Id": dest }, "value": value }),
+            Vec::new(),
+        )
+    }
+
+    fn check(
+        policy: super::SpendingPolicy,
+        call: &crate::CallInfo,
+    ) -> Result<u128, super::PolicyViolation> {
+        let mut amount = 0;
+        super::ParsedPolicy::parse(policy)
+            .unwrap()
+            .check_call(call, &mut amount)?;
+
+        Ok(amount)
+    }
+
+    fn spending_path(name: &str) -> std::path::PathBuf {
+        let spending_path = std::env::temp_dir().join(format!(
+            "nagara-spending-{}-{name}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&spending_path);
+
+        spending_path
+    }
+
+    fn daily_limit(reservation: crate::Result<super::SpendingReservation>) -> Option<u128> {
+        match reservation {
+            Err(crate::Error::PolicyViolation(super::PolicyViolation::DailyLimit {
+                spent,
+                ..
+            })) => Some(spent),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn check_call_refuses_calls_outside_the_allowlist() {
+        let policy = super::SpendingPolicy {
+            allowed_calls: Some(vec![
+                "Utility::batch_all".to_string(),
+                "Balances".to_string(),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check(policy.clone(), &transfer(BOB, serde_json::json!(10))),
+            Ok(10)
+        );
+        assert_eq!(
+            check(
+                policy.clone(),
+                &call("System", "remark", serde_json::json!({}), Vec::new())
+            ),
+            Err(super::PolicyViolation::CallNotAllowed {
+                pallet: "System".to_string(),
+                call: "remark".to_string(),
+            })
+        );
+        assert_eq!(
+            check(
+                policy,
+                &call(
+                    "Utility",
+                    "batch_all",
+                    serde_json::json!({}),
+                    vec![
+                        transfer(BOB, serde_json::json!(10)),
+                        call("Staking", "bond", serde_json::json!({}), Vec::new()),
+                    ],
+                )
+            ),
+            Err(super::PolicyViolation::CallNotAllowed {
+                pallet: "Staking".to_string(),
+                call: "bond".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn check_call_adds_nested_amounts_and_checks_recipients() {
+        let policy = super::SpendingPolicy {
+            denied_recipients: vec![ALICE.to_string()],
+            ..Default::default()
+        };
+        let batch = |dest| {
+            call(
+                "Utility",
+                "batch_all",
+                serde_json::json!({}),
+                vec![
+                    transfer(BOB, serde_json::json!(40)),
+                    transfer(dest, serde_json::json!("60")),
+                ],
+            )
+        };
+
+        assert_eq!(check(policy.clone(), &batch(BOB)), Ok(100));
+        assert_eq!(
+            check(policy, &batch(ALICE)),
+            Err(super::PolicyViolation::RecipientDenied(ALICE.to_string()))
+        );
+    }
+
+    #[test]
+    fn check_call_refuses_unchecked_amounts_under_limits() {
+        let limited = super::SpendingPolicy {
+            max_per_transaction: Some(100),
+            ..Default::default()
+        };
+        let vested_transfer = call(
+            "Vesting",
+            "vested_transfer",
+            serde_json::json!({}),
+            Vec::new(),
+        );
+        let transfer_all = call(
+            "Balances",
+            "transfer_all",
+            serde_json::json!({ "dest": { "Id": BOB }, "keep_alive": false }),
+            Vec::new(),
+        );
+        let proxy = call(
+            "Proxy",
+            "proxy",
+            serde_json::json!({}),
+            vec![transfer(BOB, serde_json::json!(10))],
+        );
+
+        for refused_call in [&vested_transfer, &transfer_all, &proxy] {
+            assert_eq!(
+                check(limited.clone(), refused_call),
+                Err(super::PolicyViolation::UncheckedAmount {
+                    pallet: refused_call.pallet.clone(),
+                    call: refused_call.call.clone(),
+                })
+            );
+            assert_eq!(check(Default::default(), refused_call).map(|_| ()), Ok(()));
+        }
+
+        assert_eq!(
+            check(
+                limited,
+                &call(
+                    "System",
+                    "remark",
+                    serde_json::json!({ "remark": "0x00" }),
+                    Vec::new()
+                )
+            ),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn daily_limit_is_reserved_until_drop() {
+        let spending = std::sync::Arc::new(super::DailySpending::default());
+        let account = subxt::utils::AccountId32([1; 32]);
+
+        let reservation = spending.reserve(account.clone(), 1, 60, 100).unwrap();
+        assert_eq!(
+            daily_limit(spending.reserve(account.clone(), 1, 50, 100)),
+            Some(60)
+        );
+
+        drop(reservation);
+        spending
+            .reserve(account.clone(), 1, 50, 100)
+            .unwrap()
+            .commit();
+        assert_eq!(
+            daily_limit(spending.reserve(account.clone(), 1, 60, 100)),
+            Some(50)
+        );
+
+        // Other accounts and the next UTC day start from nothing.
+        spending
+            .reserve(subxt::utils::AccountId32([2; 32]), 1, 100, 100)
+            .unwrap()
+            .commit();
+        spending.reserve(account, 2, 100, 100).unwrap().commit();
+    }
+
+    #[test]
+    fn spending_file_keeps_reservations_across_runs() {
+        let spending_path = spending_path("reload");
+        let account = subxt::utils::AccountId32([1; 32]);
+        let reopen =
+            || std::sync::Arc::new(super::DailySpending::open(spending_path.clone()).unwrap());
+
+        reopen()
+            .reserve(account.clone(), 1, 70, 100)
+            .unwrap()
+            .commit();
+        assert_eq!(
+            daily_limit(reopen().reserve(account.clone(), 1, 40, 100)),
+            Some(70)
+        );
+
+        // A reservation is saved before the transaction goes out, and given back on drop.
+        let spending = reopen();
+        let reservation = spending.reserve(account.clone(), 1, 20, 100).unwrap();
+        assert_eq!(
+            daily_limit(reopen().reserve(account.clone(), 1, 20, 100)),
+            Some(90)
+        );
+        drop(reservation);
+        assert_eq!(
+            daily_limit(reopen().reserve(account.clone(), 1, 40, 100)),
+            Some(70)
+        );
+
+        reopen().reserve(account, 2, 100, 100).unwrap().commit();
+
+        std::fs::remove_file(&spending_path).unwrap();
+    }
+}
@@ -0,0 +1,368 @@
+/// Length of the periods the totals of a report are grouped by, in UTC.
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ReportPeriod {
+    Day,
+    Month,
+    Year,
+}
+
+/// One ledger entry of a report, amounts are exact decimals of the token.
+///
+/// Asset amounts are left in the smallest unit of the asset, the wallet doesn't know its decimals.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReportRow {
+    pub account: String,
+    /// RFC 3339 UTC date of the block.
+    pub date: String,
+    pub block_number: u32,
+    pub block_hash: crate::BlockHash,
+    pub extrinsic_index: Option<u32>,
+    pub call: Option<String>,
+    pub kind: crate::LedgerKind,
+    /// `None` for the native token.
+    pub asset_id: Option<u32>,
+    pub incoming: bool,
+    pub counterparty: Option<String>,
+    pub amount: String,
+    pub fee: String,
+    pub status: crate::LedgerStatus,
+    pub explorer_url: String,
+}
+
+/// Movements of an account over one period, per asset.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReportTotal {
+    pub account: String,
+    /// `2023-11-30`, `2023-11` or `2023` depending on the report period.
+    pub period: String,
+    /// `None` for the native token, which also carries the fees.
+    pub asset_id: Option<u32>,
+    pub incoming: String,
+    pub outgoing: String,
+    pub fees: String,
+}
+
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AccountingReport {
+    /// Unix timestamp in milliseconds of the start of the report, included.
+    pub from_timestamp: u64,
+    /// Unix timestamp in milliseconds of the end of the report, excluded.
+    pub to_timestamp: u64,
+    pub token_symbol: String,
+    pub period: ReportPeriod,
+    pub rows: Vec<ReportRow>,
+    pub totals: Vec<ReportTotal>,
+}
+
+impl AccountingReport {
+    pub const ROWS_CSV_HEADER: &'static str = "account,date,block_number,block_hash,extrinsic_index,call,kind,asset_id,direction,counterparty,amount,fee,status,explorer_url";
+    pub const TOTALS_CSV_HEADER: &'static str = "account,period,asset_id,incoming,outgoing,fees";
+
+    pub fn to_json(&self) -> crate::Result<String> {
+        serde_json::to_string_pretty(self).map_err(|err| crate::Error::OtherError(err.to_string()))
+    }
+
+    /// One line per row after [`Self::ROWS_CSV_HEADER`].
+    pub fn rows_to_csv(&self) -> String {
+        let mut csv = format!("{}\n", Self::ROWS_CSV_HEADER);
+
+        for row in &self.rows {
+            let kind = match row.kind {
+                crate::LedgerKind::Transfer => "transfer",
+                crate::LedgerKind::AssetTransfer { .. } => "asset_transfer",
+                crate::LedgerKind::ContractCall => "contract_call",
+                crate::LedgerKind::Fee => "fee",
+            };
+            let status = match row.status {
+                crate::LedgerStatus::Success => "success",
+                crate::LedgerStatus::Failed => "failed",
+            };
+            let fields = [
+                row.account.clone(),
+                row.date.clone(),
+                row.block_number.to_string(),
+                format!("0x{}", hex::encode(row.block_hash)),
+                optional_field(row.extrinsic_index),
+                row.call.clone().unwrap_or_default(),
+                kind.to_string(),
+                optional_field(row.asset_id),
+                if row.incoming { "in" } else { "out" }.to_string(),
+                row.counterparty.clone().unwrap_or_default(),
+                row.amount.clone(),
+                row.fee.clone(),
+                status.to_string(),
+                row.explorer_url.clone(),
+            ];
+            push_csv_line(&mut csv, &fields);
+        }
+
+        csv
+    }
+
+    /// One line per total after [`Self::TOTALS_CSV_HEADER`].
+    pub fn totals_to_csv(&self) -> String {
+        let mut csv = format!("{}\n", Self::TOTALS_CSV_HEADER);
+
+        for total in &self.totals {
+            let fields = [
+                total.account.clone(),
+                total.period.clone(),
+                optional_field(total.asset_id),
+                total.incoming.clone(),
+                total.outgoing.clone(),
+                total.fees.clone(),
+            ];
+            push_csv_line(&mut csv, &fields);
+        }
+
+        csv
+    }
+}
+
+/// Exact decimal of an amount in the smallest unit, without trailing zeros.
+fn format_amount(amount: u128, decimals: u8) -> String {
+    let digits = format!("{amount:0>width$}", width = decimals as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+fn optional_field<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn push_csv_line(csv: &mut String, fields: &[String]) {
+    let fields = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+}
+
+/// UTC calendar date and time of a unix timestamp in milliseconds.
+fn utc_date_time(timestamp_millis: u64) -> (i64, u32, u32, u64) {
+    let seconds = timestamp_millis / 1000;
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = (if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, seconds % 86400)
+}
+
+fn rfc3339_date(timestamp_millis: u64) -> String {
+    let (year, month, day, seconds_of_day) = utc_date_time(timestamp_millis);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+fn period_name(timestamp_millis: u64, period: ReportPeriod) -> String {
+    let (year, month, day, _) = utc_date_time(timestamp_millis);
+
+    match period {
+        ReportPeriod::Day => format!("{year:04}-{month:02}-{day:02}"),
+        ReportPeriod::Month => format!("{year:04}-{month:02}"),
+        ReportPeriod::Year => format!("{year:04}"),
+    }
+}
+
+impl crate::WalletInstance {
+    /// Ledger entries of the accounts with a timestamp from `from_timestamp` included to
+    /// `to_timestamp` excluded, with their totals per period.
+    ///
+    /// Only finalized blocks are reported. The history comes from the history index when it covers
+    /// every account, otherwise each block of the range is read once for all of them.
+    pub async fn accounting_report<A: core::convert::AsRef<str>>(
+        &self,
+        account_addresses: &[A],
+        from_timestamp: u64,
+        to_timestamp: u64,
+        period: ReportPeriod,
+    ) -> crate::Result<AccountingReport> {
+        if from_timestamp >= to_timestamp {
+            return Err(crate::Error::InvalidArgument(
+                "Report must end after it starts".to_string(),
+            ));
+        }
+
+        let (from_block, _) = self.block_at_timestamp(from_timestamp).await?;
+        let (to_block, _) = self.block_at_timestamp(to_timestamp).await?;
+        let decimals = self.network.token_decimals;
+        let mut rows = Vec::new();
+        let mut totals =
+            std::collections::BTreeMap::<(String, String, Option<u32>), (u128, u128, u128)>::new();
+
+        let account_addresses = account_addresses
+            .iter()
+            .map(|account_address| account_address.as_ref())
+            .collect::<Vec<_>>();
+
+        for (account_address, entries) in account_addresses.iter().zip(
+            self.report_entries(&account_addresses, from_block, to_block)
+                .await?,
+        ) {
+            for entry in entries {
+                if entry.timestamp < from_timestamp || entry.timestamp >= to_timestamp {
+                    continue;
+                }
+
+                let asset_id = match entry.kind {
+                    crate::LedgerKind::AssetTransfer { asset_id } => Some(asset_id),
+                    _ => None,
+                };
+                let amount = match asset_id {
+                    Some(_) => entry.amount.to_string(),
+                    None => format_amount(entry.amount, decimals),
+                };
+                let period_name = period_name(entry.timestamp, period);
+                let total = totals
+                    .entry((account_address.to_string(), period_name.clone(), asset_id))
+                    .or_default();
+
+                if entry.incoming {
+                    total.0 += entry.amount;
+                } else {
+                    total.1 += entry.amount;
+                }
+
+                totals
+                    .entry((account_address.to_string(), period_name, None))
+                    .or_default()
+                    .2 += entry.fee;
+                rows.push(ReportRow {
+                    account: account_address.to_string(),
+                    date: rfc3339_date(entry.timestamp),
+                    block_number: entry.block_number,
+                    block_hash: entry.block_hash,
+                    extrinsic_index: entry.extrinsic_index,
+                    call: entry.call,
+                    kind: entry.kind,
+                    asset_id,
+                    incoming: entry.incoming,
+                    counterparty: entry.counterparty,
+                    amount,
+                    fee: format_amount(entry.fee, decimals),
+                    status: entry.status,
+                    explorer_url: self.block_url(entry.block_hash),
+                });
+            }
+        }
+
+        let totals = totals
+            .into_iter()
+            .map(
+                |((account, period, asset_id), (incoming, outgoing, fees))| {
+                    let format_total = |total| match asset_id {
+                        Some(_) => u128::to_string(&total),
+                        None => format_amount(total, decimals),
+                    };
+
+                    ReportTotal {
+                        account,
+                        period,
+                        asset_id,
+                        incoming: format_total(incoming),
+                        outgoing: format_total(outgoing),
+                        fees: format_amount(fees, decimals),
+                    }
+                },
+            )
+            .collect();
+
+        Ok(AccountingReport {
+            from_timestamp,
+            to_timestamp,
+            token_symbol: self.network.token_symbol.clone(),
+            period,
+            rows,
+            totals,
+        })
+    }
+
+    /// Ledger entries of each account from `from_block` to `to_block`, both included.
+    async fn report_entries(
+        &self,
+        account_addresses: &[&str],
+        from_block: u32,
+        to_block: u32,
+    ) -> crate::Result<Vec<Vec<crate::LedgerEntry>>> {
+        #[cfg(feature = "index-db")]
+        if let Some(history_index) = &self.history_index {
+            let indexed_entries = account_addresses
+                .iter()
+                .map(|account_address| {
+                    history_index.account_history(account_address, from_block, to_block)
+                })
+                .collect::<crate::Result<Option<Vec<_>>>>()?;
+
+            if let Some(indexed_entries) = indexed_entries {
+                return Ok(indexed_entries);
+            }
+        }
+
+        let accounts = account_addresses
+            .iter()
+            .map(|account_address| crate::compat::parse_account(account_address))
+            .collect::<crate::Result<Vec<_>>>()?;
+        // The same account may be listed twice, under different address formats.
+        let mut unique_accounts = accounts.clone();
+        unique_accounts.sort();
+        unique_accounts.dedup();
+        let runtime_mode = self.compatible_runtime_mode()?;
+        let mut entries = vec![Vec::new(); accounts.len()];
+
+        for block_number in from_block..=to_block {
+            let block_hash = self
+                .block_hash(crate::BlockId::Number(block_number))
+                .await?;
+
+            for (account, entry) in crate::history::block_ledgers(
+                &self.client,
+                runtime_mode,
+                self.network.ss58_prefix,
+                &unique_accounts,
+                block_number,
+                block_hash,
+            )
+            .await?
+            {
+                for (account_position, _) in accounts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, listed_account)| **listed_account == account)
+                {
+                    entries[account_position].push(entry.clone());
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+}
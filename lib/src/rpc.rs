@@ -0,0 +1,540 @@
+/// RPC client spreading over several endpoints of the same network.
+///
+/// A request failing while the current endpoint does not answer `system_health` anymore makes the
+/// client reconnect, moving to the next endpoint with an exponential backoff, and retry a bounded
+/// number of times. [`FailoverRpcClient::monitor_health`] also checks the endpoint periodically so
+/// an idle client moves before its next request fails. Block header, storage and runtime version
+/// subscriptions are opened again on the new connection, other subscriptions (like transaction
+/// watches) end with the error. The ones a healthy endpoint ends are opened again with the same
+/// backoff.
+#[derive(Clone)]
+pub(crate) struct FailoverRpcClient {
+    shared: std::sync::Arc<SharedConnection>,
+}
+
+struct SharedConnection {
+    urls: Vec<String>,
+    current: std::sync::RwLock<Connection>,
+    reconnecting: futures::lock::Mutex<()>,
+}
+
+#[derive(Clone)]
+struct Connection {
+    generation: u64,
+    endpoint_index: usize,
+    client: std::sync::Arc<dyn subxt::rpc::RpcClientT>,
+}
+
+struct ResubscribeState {
+    shared: std::sync::Arc<SharedConnection>,
+    sub: String,
+    params: Option<Box<subxt::rpc::RawValue>>,
+    unsub: String,
+    stream: subxt::rpc::RpcSubscriptionStream,
+    /// Wait before subscribing again to an endpoint which ended the stream while healthy.
+    backoff: core::time::Duration,
+    ended: bool,
+}
+
+impl FailoverRpcClient {
+    #[cfg(not(test))]
+    const INITIAL_BACKOFF: core::time::Duration = core::time::Duration::from_millis(500);
+    #[cfg(test)]
+    const INITIAL_BACKOFF: core::time::Duration = core::time::Duration::from_millis(1);
+    const MAX_BACKOFF: core::time::Duration = core::time::Duration::from_secs(30);
+    const RECONNECT_ROUNDS: usize = 3;
+    const MAX_REQUEST_ATTEMPTS: usize = 3;
+    #[cfg(not(test))]
+    const HEALTH_CHECK_INTERVAL: core::time::Duration = core::time::Duration::from_secs(15);
+    #[cfg(test)]
+    const HEALTH_CHECK_INTERVAL: core::time::Duration = core::time::Duration::from_millis(10);
+    const RESUBSCRIBE_PREFIXES: [&'static str; 3] = ["chain_", "state_", "grandpa_"];
+
+    pub(crate) async fn connect<U: core::convert::AsRef<str>>(urls: &[U]) -> crate::Result<Self> {
+        let urls: Vec<String> = urls.iter().map(|url| url.as_ref().to_string()).collect();
+
+        if urls.is_empty() {
+            return Err(crate::Error::NoHealthyEndpoint);
+        }
+
+        for (endpoint_index, url) in urls.iter().enumerate() {
+            if let Some(client) = SharedConnection::open(url).await {
+                return Ok(Self {
+                    shared: std::sync::Arc::new(SharedConnection {
+                        current: std::sync::RwLock::new(Connection {
+                            generation: 0,
+                            endpoint_index,
+                            client,
+                        }),
+                        urls,
+                        reconnecting: futures::lock::Mutex::new(()),
+                    }),
+                });
+            }
+        }
+
+        Err(crate::Error::NoHealthyEndpoint)
+    }
+
+    pub(crate) fn current_url(&self) -> String {
+        let current = self.shared.current();
+
+        self.shared.urls[current.endpoint_index].clone()
+    }
+
+    /// Check the current endpoint every [`Self::HEALTH_CHECK_INTERVAL`] and reconnect as soon as
+    /// it stops answering, to run in the background.
+    pub(crate) async fn monitor_health(self) {
+        loop {
+            futures_timer::Delay::new(Self::HEALTH_CHECK_INTERVAL).await;
+            let connection = self.shared.current();
+
+            if SharedConnection::is_healthy(&connection).await {
+                continue;
+            }
+
+            let url = &self.shared.urls[connection.endpoint_index];
+            nagara_logging::info!("Endpoint {url} failed its health check");
+
+            if let Err(err) = self.shared.reconnect(connection.generation).await {
+                nagara_logging::info!("Health check could not reconnect: {err}");
+            }
+        }
+    }
+}
+
+impl SharedConnection {
+    fn current(&self) -> Connection {
+        self.current.read().unwrap().clone()
+    }
+
+    async fn open(url: &str) -> Option<std::sync::Arc<dyn subxt::rpc::RpcClientT>> {
+        let client = subxt::client::default_rpc_client(url).await.ok()?;
+
+        if client.request_raw("system_health", None).await.is_err() {
+            return None;
+        }
+
+        Some(std::sync::Arc::new(client))
+    }
+
+    async fn is_healthy(connection: &Connection) -> bool {
+        connection
+            .client
+            .request_raw("system_health", None)
+            .await
+            .is_ok()
+    }
+
+    /// Replace the connection of `failed_generation` with one to the next healthy endpoint.
+    async fn reconnect(&self, failed_generation: u64) -> Result<(), subxt::error::RpcError> {
+        let _reconnecting = self.reconnecting.lock().await;
+        let failed = self.current();
+
+        if failed.generation != failed_generation {
+            return Ok(());
+        }
+
+        let mut backoff = FailoverRpcClient::INITIAL_BACKOFF;
+
+        for attempt in 0..self.urls.len() * FailoverRpcClient::RECONNECT_ROUNDS {
+            let endpoint_index = (failed.endpoint_index + 1 + attempt) % self.urls.len();
+            let url = &self.urls[endpoint_index];
+
+            if let Some(client) = Self::open(url).await {
+                nagara_logging::info!("Reconnected to {url}");
+                *self.current.write().unwrap() = Connection {
+                    generation: failed_generation + 1,
+                    endpoint_index,
+                    client,
+                };
+
+                return Ok(());
+            }
+
+            nagara_logging::info!("Endpoint {url} is unreachable, retrying in {backoff:?}");
+            futures_timer::Delay::new(backoff).await;
+            backoff = (backoff * 2).min(FailoverRpcClient::MAX_BACKOFF);
+        }
+
+        Err(subxt::error::RpcError::ClientError(Box::new(
+            crate::Error::NoHealthyEndpoint,
+        )))
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+    ) -> Result<Box<subxt::rpc::RawValue>, subxt::error::RpcError> {
+        let mut attempt = 1;
+
+        loop {
+            let connection = self.current();
+            let result = connection.client.request_raw(method, params.clone()).await;
+
+            match result {
+                Err(err)
+                    if attempt < FailoverRpcClient::MAX_REQUEST_ATTEMPTS
+                        && !Self::is_healthy(&connection).await =>
+                {
+                    nagara_logging::info!("Request {method} failed on a broken connection: {err}");
+                    self.reconnect(connection.generation).await?;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn subscribe(
+        &self,
+        sub: &str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+        unsub: &str,
+    ) -> Result<subxt::rpc::RpcSubscription, subxt::error::RpcError> {
+        let mut attempt = 1;
+
+        loop {
+            let connection = self.current();
+            let result = connection
+                .client
+                .subscribe_raw(sub, params.clone(), unsub)
+                .await;
+
+            match result {
+                Err(err)
+                    if attempt < FailoverRpcClient::MAX_REQUEST_ATTEMPTS
+                        && !Self::is_healthy(&connection).await =>
+                {
+                    nagara_logging::info!(
+                        "Subscription {sub} failed on a broken connection: {err}"
+                    );
+                    self.reconnect(connection.generation).await?;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl subxt::rpc::RpcClientT for FailoverRpcClient {
+    fn request_raw<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+    ) -> subxt::rpc::RpcFuture<'a, Box<subxt::rpc::RawValue>> {
+        Box::pin(self.shared.request(method, params))
+    }
+
+    fn subscribe_raw<'a>(
+        &'a self,
+        sub: &'a str,
+        params: Option<Box<subxt::rpc::RawValue>>,
+        unsub: &'a str,
+    ) -> subxt::rpc::RpcFuture<'a, subxt::rpc::RpcSubscription> {
+        Box::pin(async move {
+            let subscription = self.shared.subscribe(sub, params.clone(), unsub).await?;
+            let resubscribable = FailoverRpcClient::RESUBSCRIBE_PREFIXES
+                .iter()
+                .any(|prefix| sub.starts_with(prefix));
+
+            if !resubscribable {
+                return Ok(subscription);
+            }
+
+            let state = ResubscribeState {
+                shared: self.shared.clone(),
+                sub: sub.to_string(),
+                params,
+                unsub: unsub.to_string(),
+                stream: subscription.stream,
+                backoff: FailoverRpcClient::INITIAL_BACKOFF,
+                ended: false,
+            };
+            let stream = futures::stream::unfold(state, |mut state| async move {
+                if state.ended {
+                    return None;
+                }
+
+                loop {
+                    match futures::StreamExt::next(&mut state.stream).await {
+                        Some(Ok(item)) => {
+                            state.backoff = FailoverRpcClient::INITIAL_BACKOFF;
+
+                            return Some((Ok(item), state));
+                        }
+                        Some(Err(_)) | None => {
+                            // A broken connection is replaced by `subscribe` with its own
+                            // backoff, a healthy endpoint would be resubscribed in a hot loop.
+                            if SharedConnection::is_healthy(&state.shared.current()).await {
+                                nagara_logging::info!(
+                                    "Subscription {} ended, resubscribing in {:?}",
+                                    state.sub,
+                                    state.backoff
+                                );
+                                futures_timer::Delay::new(state.backoff).await;
+                                state.backoff =
+                                    (state.backoff * 2).min(FailoverRpcClient::MAX_BACKOFF);
+                            } else {
+                                nagara_logging::info!(
+                                    "Subscription {} dropped, resubscribing",
+                                    state.sub
+                                );
+                            }
+
+                            let resubscribed = state
+                                .shared
+                                .subscribe(&state.sub, state.params.clone(), &state.unsub)
+                                .await;
+
+                            match resubscribed {
+                                Ok(subscription) => state.stream = subscription.stream,
+                                Err(err) => {
+                                    state.ended = true;
+
+                                    return Some((Err(err), state));
+                                }
+                            }
+                        }
+                    }
+                }
+            });
+
+            // Each resubscription gets a new id from its endpoint, none of them stays valid.
+            Ok(subxt::rpc::RpcSubscription {
+                stream: Box::pin(stream),
+                id: None,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Start a node answering `system_health`, `system_name` with its own URL and a
+    /// `chain_subscribeNewHeads` subscription sending its URL every few milliseconds.
+    async fn mock_node() -> (String, jsonrpsee::server::ServerHandle) {
+        let server = jsonrpsee::server::ServerBuilder::default()
+            .build("127.0.0.1:0")
+            .await
+            .unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let mut module = jsonrpsee::RpcModule::new(url.clone());
+        module
+            .register_method("system_health", |_, _| {
+                serde_json::json!({ "peers": 1, "isSyncing": false, "shouldHavePeers": true })
+            })
+            .unwrap();
+        module
+            .register_method("system_name", |_, url: &String| url.clone())
+            .unwrap();
+        module
+            .register_subscription(
+                "chain_subscribeNewHeads",
+                "chain_newHead",
+                "chain_unsubscribeNewHeads",
+                |_, pending, url| send_heads(pending, url),
+            )
+            .unwrap();
+
+        (url, server.start(module))
+    }
+
+    async fn send_heads(
+        pending: jsonrpsee::PendingSubscriptionSink,
+        url: std::sync::Arc<String>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let sink = pending.accept().await?;
+
+        loop {
+            let message = jsonrpsee::SubscriptionMessage::from_json(&*url)?;
+            sink.send(message).await?;
+            tokio::time::sleep(core::time::Duration::from_millis(5)).await;
+        }
+    }
+
+    /// Start a node answering `system_health` and ending every `chain_subscribeFinalizedHeads`
+    /// subscription right away, with the number of subscriptions it got.
+    async fn ending_node() -> (
+        String,
+        std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        jsonrpsee::server::ServerHandle,
+    ) {
+        let server = jsonrpsee::server::ServerBuilder::default()
+            .build("127.0.0.1:0")
+            .await
+            .unwrap();
+        let url = format!("ws://{}", server.local_addr().unwrap());
+        let subscriptions = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut module = jsonrpsee::RpcModule::new(subscriptions.clone());
+        module
+            .register_method("system_health", |_, _| {
+                serde_json::json!({ "peers": 1, "isSyncing": false, "shouldHavePeers": true })
+            })
+            .unwrap();
+        module
+            .register_subscription(
+                "chain_subscribeFinalizedHeads",
+                "chain_finalizedHead",
+                "chain_unsubscribeFinalizedHeads",
+                |_, pending, subscriptions| end_subscription(pending, (*subscriptions).clone()),
+            )
+            .unwrap();
+
+        (url, subscriptions, server.start(module))
+    }
+
+    async fn end_subscription(
+        pending: jsonrpsee::PendingSubscriptionSink,
+        subscriptions: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        subscriptions.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        pending.accept().await?;
+
+        Err("Subscription ended".into())
+    }
+
+    async fn stop(node: jsonrpsee::server::ServerHandle) {
+        node.stop().unwrap();
+        node.stopped().await;
+    }
+
+    async fn system_name(client: &super::FailoverRpcClient) -> crate::Result<String> {
+        let name = subxt::rpc::RpcClientT::request_raw(client, "system_name", None)
+            .await
+            .map_err(subxt::Error::from)?;
+
+        Ok(serde_json::from_str(name.get()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn connect_skips_unreachable_endpoints() {
+        let (url, _node) = mock_node().await;
+        let client = super::FailoverRpcClient::connect(&["ws://127.0.0.1:1", url.as_str()])
+            .await
+            .unwrap();
+
+        assert_eq!(client.current_url(), url);
+        assert_eq!(system_name(&client).await.unwrap(), url);
+    }
+
+    #[tokio::test]
+    async fn request_fails_over_to_the_next_endpoint() {
+        let (first_url, first_node) = mock_node().await;
+        let (second_url, _second_node) = mock_node().await;
+        let client = super::FailoverRpcClient::connect(&[&first_url, &second_url])
+            .await
+            .unwrap();
+        assert_eq!(system_name(&client).await.unwrap(), first_url);
+
+        stop(first_node).await;
+
+        assert_eq!(system_name(&client).await.unwrap(), second_url);
+        assert_eq!(client.current_url(), second_url);
+    }
+
+    #[tokio::test]
+    async fn request_gives_up_without_healthy_endpoint() {
+        let (first_url, first_node) = mock_node().await;
+        let (second_url, second_node) = mock_node().await;
+        let client = super::FailoverRpcClient::connect(&[&first_url, &second_url])
+            .await
+            .unwrap();
+
+        stop(first_node).await;
+        stop(second_node).await;
+
+        let result =
+            tokio::time::timeout(core::time::Duration::from_secs(10), system_name(&client))
+                .await
+                .expect("request retried without limit");
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn health_monitor_reconnects_idle_client() {
+        let (first_url, first_node) = mock_node().await;
+        let (second_url, _second_node) = mock_node().await;
+        let client = super::FailoverRpcClient::connect(&[&first_url, &second_url])
+            .await
+            .unwrap();
+        let health_monitor = tokio::spawn(client.clone().monitor_health());
+
+        stop(first_node).await;
+        tokio::time::sleep(core::time::Duration::from_millis(500)).await;
+        health_monitor.abort();
+
+        assert_eq!(client.current_url(), second_url);
+    }
+
+    #[tokio::test]
+    async fn subscription_resumes_on_the_next_endpoint() {
+        let (first_url, first_node) = mock_node().await;
+        let (second_url, _second_node) = mock_node().await;
+        let client = super::FailoverRpcClient::connect(&[&first_url, &second_url])
+            .await
+            .unwrap();
+        let mut subscription = subxt::rpc::RpcClientT::subscribe_raw(
+            &client,
+            "chain_subscribeNewHeads",
+            None,
+            "chain_unsubscribeNewHeads",
+        )
+        .await
+        .unwrap();
+        assert_eq!(subscription.id, None);
+
+        let next_url = |item: Option<Result<Box<subxt::rpc::RawValue>, _>>| {
+            serde_json::from_str::<String>(item.unwrap().unwrap().get()).unwrap()
+        };
+        let item = futures::StreamExt::next(&mut subscription.stream).await;
+        assert_eq!(next_url(item), first_url);
+
+        stop(first_node).await;
+
+        loop {
+            let item = tokio::time::timeout(
+                core::time::Duration::from_secs(10),
+                futures::StreamExt::next(&mut subscription.stream),
+            )
+            .await
+            .expect("subscription was not resumed");
+
+            if next_url(item) == second_url {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn subscription_ended_by_healthy_endpoint_backs_off() {
+        let (url, subscriptions, _node) = ending_node().await;
+        let client = super::FailoverRpcClient::connect(&[&url]).await.unwrap();
+        let mut subscription = subxt::rpc::RpcClientT::subscribe_raw(
+            &client,
+            "chain_subscribeFinalizedHeads",
+            None,
+            "chain_unsubscribeFinalizedHeads",
+        )
+        .await
+        .unwrap();
+        let consumer = tokio::spawn(async move {
+            while futures::StreamExt::next(&mut subscription.stream)
+                .await
+                .is_some()
+            {}
+        });
+
+        tokio::time::sleep(core::time::Duration::from_millis(200)).await;
+        consumer.abort();
+
+        // Backing off from 1ms, a hot loop would subscribe hundreds of times.
+        let subscriptions = subscriptions.load(std::sync::atomic::Ordering::Relaxed);
+        assert!(
+            (2..=12).contains(&subscriptions),
+            "{subscriptions} subscriptions"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+/// GRANDPA authority set change scheduled by the runtime.
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub struct PendingAuthorityChange {
+    pub scheduled_at: u32,
+    pub delay: u32,
+    pub next_authority_count: usize,
+    /// Median last finalized block of a forced change, `None` for a standard one.
+    pub forced: Option<u32>,
+}
+
+/// Heights, consensus state and node health at the best block.
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub struct ChainStatus {
+    pub best_number: u32,
+    pub best_hash: crate::BlockHash,
+    pub finalized_number: u32,
+    pub finalized_hash: crate::BlockHash,
+    /// Best blocks not finalized yet.
+    pub finality_lag: u32,
+    /// The heads are the last known ones, see [`crate::WalletInstance::chain_heads_stale`].
+    pub heads_stale: bool,
+    pub aura_authorities: Vec<String>,
+    pub current_slot: u64,
+    pub grandpa_set_id: u64,
+    /// `(delay, median last finalized block)` of the forced change GRANDPA scheduled to recover.
+    pub grandpa_stalled: Option<(u32, u32)>,
+    pub grandpa_pending_change: Option<PendingAuthorityChange>,
+    /// Average over the last [`ChainStatus::BLOCK_TIME_WINDOW`] blocks, `None` on a younger chain.
+    pub average_block_time_millis: Option<u64>,
+    pub peers: usize,
+    pub is_syncing: bool,
+    pub should_have_peers: bool,
+}
+
+impl ChainStatus {
+    pub const BLOCK_TIME_WINDOW: u32 = 100;
+}
+
+/// Raised by [`crate::WalletInstance::watch_finality`] when the finality lag crosses its limit.
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+pub enum FinalityAlert {
+    Stalled {
+        best_number: u32,
+        finalized_number: u32,
+    },
+    Recovered {
+        best_number: u32,
+        finalized_number: u32,
+    },
+}
+
+struct FinalityWatch {
+    head_updates: futures::channel::mpsc::UnboundedReceiver<crate::ChainHeads>,
+    max_lag: u32,
+    stalled: bool,
+}
+
+impl FinalityWatch {
+    async fn next_alert(&mut self) -> Option<FinalityAlert> {
+        loop {
+            let chain_heads = futures::StreamExt::next(&mut self.head_updates).await?;
+            let best_number = chain_heads.best_number();
+            let finalized_number = chain_heads.finalized_number();
+            let stalled = chain_heads.finality_lag() > self.max_lag;
+
+            if stalled == self.stalled {
+                continue;
+            }
+
+            self.stalled = stalled;
+
+            return Some(if stalled {
+                FinalityAlert::Stalled {
+                    best_number,
+                    finalized_number,
+                }
+            } else {
+                FinalityAlert::Recovered {
+                    best_number,
+                    finalized_number,
+                }
+            });
+        }
+    }
+}
+
+impl crate::WalletInstance {
+    pub async fn chain_status(&self) -> crate::Result<ChainStatus> {
+        let runtime_mode = self.compatible_runtime_mode()?;
+        let chain_heads = self.chain_heads();
+        let best_hash = chain_heads.best_hash;
+        let best_number = chain_heads.best_number();
+        let aura_authorities = self
+            .aura_authorities(runtime_mode, best_hash)
+            .await?
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let storage = self.client.storage().at(best_hash);
+        let (current_slot, grandpa_set_id, grandpa_stalled, grandpa_pending_change) =
+            if runtime_mode == crate::RuntimeMode::Dynamic {
+                let current_slot = storage
+                    .fetch_or_default(&subxt::dynamic::storage_root("Aura", "CurrentSlot"))
+                    .await?
+                    .to_value()?;
+                let grandpa_set_id = storage
+                    .fetch_or_default(&subxt::dynamic::storage_root("Grandpa", "CurrentSetId"))
+                    .await?
+                    .to_value()?;
+                let grandpa_stalled = match storage
+                    .fetch(&subxt::dynamic::storage_root("Grandpa", "Stalled"))
+                    .await?
+                {
+                    Some(stalled) => {
+                        let stalled = stalled.to_value()?;
+                        let stalled_field = |index: usize| {
+                            subxt::dynamic::At::at(&stalled, index)
+                                .and_then(crate::compat::dynamic_u128)
+                                .unwrap_or_default() as u32
+                        };
+
+                        Some((stalled_field(0), stalled_field(1)))
+                    }
+                    None => None,
+                };
+                let grandpa_pending_change = match storage
+                    .fetch(&subxt::dynamic::storage_root("Grandpa", "PendingChange"))
+                    .await?
+                {
+                    Some(pending_change) => {
+                        let pending_change = pending_change.to_value()?;
+                        let field = |name: &str| subxt::dynamic::At::at(&pending_change, name);
+                        let u32_field = |name: &str| {
+                            field(name)
+                                .and_then(crate::compat::dynamic_u128)
+                                .unwrap_or_default() as u32
+                        };
+                        let next_authority_count = field("next_authorities")
+                            .and_then(|next_authorities| {
+                                subxt::dynamic::At::at(next_authorities, 0)
+                            })
+                            .map_or(0, |next_authorities| match &next_authorities.value {
+                                subxt::ext::scale_value::ValueDef::Composite(next_authorities) => {
+                                    next_authorities.len()
+                                }
+                                _ => 0,
+                            });
+                        let forced = field("forced")
+                            .and_then(|forced| subxt::dynamic::At::at(forced, 0))
+                            .and_then(crate::compat::dynamic_u128)
+                            .map(|forced| forced as u32);
+
+                        Some(PendingAuthorityChange {
+                            scheduled_at: u32_field("scheduled_at"),
+                            delay: u32_field("delay"),
+                            next_authority_count,
+                            forced,
+                        })
+                    }
+                    None => None,
+                };
+
+                (
+                    crate::compat::dynamic_u128(&current_slot).unwrap_or_default() as u64,
+                    crate::compat::dynamic_u128(&grandpa_set_id).unwrap_or_default() as u64,
+                    grandpa_stalled,
+                    grandpa_pending_change,
+                )
+            } else {
+                let storage_api = crate::metadata::nagara::api::storage();
+                let current_slot = storage
+                    .fetch_or_default(&storage_api.aura().current_slot())
+                    .await?;
+                let grandpa_set_id = storage
+                    .fetch_or_default(&storage_api.grandpa().current_set_id())
+                    .await?;
+                let grandpa_stalled = storage.fetch(&storage_api.grandpa().stalled()).await?;
+                let grandpa_pending_change = storage
+                    .fetch(&storage_api.grandpa().pending_change())
+                    .await?
+                    .map(|pending_change| PendingAuthorityChange {
+                        scheduled_at: pending_change.scheduled_at,
+                        delay: pending_change.delay,
+                        next_authority_count: pending_change.next_authorities.0.len(),
+                        forced: pending_change.forced,
+                    });
+
+                (
+                    current_slot.0,
+                    grandpa_set_id,
+                    grandpa_stalled,
+                    grandpa_pending_change,
+                )
+            };
+        let average_block_time_millis =
+            match best_number.checked_sub(ChainStatus::BLOCK_TIME_WINDOW) {
+                Some(window_start) if window_start > 0 => {
+                    let window_start_hash = self
+                        .block_hash(crate::BlockId::Number(window_start))
+                        .await?;
+                    let window_start_time = self.timestamp_at(window_start_hash).await?;
+                    let best_time = self.timestamp_at(best_hash).await?;
+
+                    Some(
+                        best_time.saturating_sub(window_start_time)
+                            / ChainStatus::BLOCK_TIME_WINDOW as u64,
+                    )
+                }
+                _ => None,
+            };
+        let health = self.client.rpc().system_health().await?;
+
+        Ok(ChainStatus {
+            best_number,
+            best_hash,
+            finalized_number: chain_heads.finalized_number(),
+            finalized_hash: chain_heads.finalized_hash,
+            finality_lag: chain_heads.finality_lag(),
+            heads_stale: self.chain_heads_stale(),
+            aura_authorities,
+            current_slot,
+            grandpa_set_id,
+            grandpa_stalled,
+            grandpa_pending_change,
+            average_block_time_millis,
+            peers: health.peers,
+            is_syncing: health.is_syncing,
+            should_have_peers: health.should_have_peers,
+        })
+    }
+
+    /// Alert once when more than `max_lag` best blocks are not finalized, and once again when
+    /// finality caught up, checked every time the best or the finalized head moves.
+    pub async fn watch_finality(
+        &self,
+        max_lag: u32,
+    ) -> crate::Result<impl futures::Stream<Item = crate::Result<FinalityAlert>> + Send + 'static>
+    {
+        let watch = FinalityWatch {
+            head_updates: self.chain_head_updates(),
+            max_lag,
+            stalled: false,
+        };
+
+        Ok(futures::stream::unfold(watch, |mut watch| async move {
+            let alert = watch.next_alert().await?;
+
+            Some((Ok(alert), watch))
+        }))
+    }
+}
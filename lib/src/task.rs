@@ -0,0 +1,55 @@
+/// Run `future` in the background until it completes or the returned handle aborts it.
+///
+/// Panics outside a Tokio runtime, [`crate::WalletInstance::create_with_network`] checks there is
+/// one before spawning anything.
+#[cfg(not(feature = "wasm32"))]
+pub(crate) fn spawn<F>(future: F) -> futures::future::AbortHandle
+where
+    F: core::future::Future<Output = ()> + Send + 'static,
+{
+    let (future, abort_handle) = futures::future::abortable(future);
+    tokio::spawn(future);
+
+    abort_handle
+}
+
+/// Run `future` on the browser event loop until it completes or the returned handle aborts it.
+#[cfg(feature = "wasm32")]
+pub(crate) fn spawn<F>(future: F) -> futures::future::AbortHandle
+where
+    F: core::future::Future<Output = ()> + 'static,
+{
+    let (future, abort_handle) = futures::future::abortable(future);
+    wasm_bindgen_futures::spawn_local(async move {
+        let _ = future.await;
+    });
+
+    abort_handle
+}
+
+/// Listeners of background events, each one gets its own unbounded channel.
+pub(crate) struct Listeners<T: Clone> {
+    senders: std::sync::Mutex<Vec<futures::channel::mpsc::UnboundedSender<T>>>,
+}
+
+impl<T: Clone> Listeners<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            senders: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> futures::channel::mpsc::UnboundedReceiver<T> {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        self.senders.lock().unwrap().push(sender);
+
+        receiver
+    }
+
+    pub(crate) fn emit(&self, event: T) {
+        self.senders
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.unbounded_send(event.clone()).is_ok());
+    }
+}
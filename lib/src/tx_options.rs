@@ -0,0 +1,114 @@
+pub(crate) type TxParams = <subxt::config::polkadot::PolkadotExtrinsicParams<
+    subxt::PolkadotConfig,
+> as subxt::config::ExtrinsicParams<subxt::PolkadotConfig>>::OtherParams;
+pub(crate) type SignedExtrinsic = subxt::tx::SubmittableExtrinsic<
+    subxt::PolkadotConfig,
+    subxt::OnlineClient<subxt::PolkadotConfig>,
+>;
+pub(crate) type TxProgress =
+    subxt::tx::TxProgress<subxt::PolkadotConfig, subxt::OnlineClient<subxt::PolkadotConfig>>;
+
+/// Extrinsic parameters applied when signing a transaction.
+///
+/// The default value signs an immortal transaction without tip, using the next nonce known by the
+/// chain, the same as `sign_and_submit_then_watch_default`.
+#[derive(Clone, core::fmt::Debug, Default)]
+pub struct TxOptions {
+    tip: u128,
+    era_period: Option<u64>,
+    nonce: Option<u64>,
+    target_block: Option<crate::BlockHash>,
+    dry_run: bool,
+}
+
+impl TxOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tip given to the block author, in the smallest unit of the native token.
+    pub fn tip(mut self, tip: u128) -> Self {
+        self.tip = tip;
+
+        self
+    }
+
+    /// Make the transaction valid only for `era_period` blocks after the target block.
+    pub fn mortal(mut self, era_period: u64) -> Self {
+        self.era_period = Some(era_period);
+
+        self
+    }
+
+    /// Make the transaction valid forever (until its nonce is used).
+    pub fn immortal(mut self) -> Self {
+        self.era_period = None;
+
+        self
+    }
+
+    /// Sign with this nonce instead of asking the chain for the next one.
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+
+        self
+    }
+
+    /// Block the mortal era starts from, defaults to the latest block.
+    pub fn at_block(mut self, block_hash: crate::BlockHash) -> Self {
+        self.target_block = Some(block_hash);
+
+        self
+    }
+
+    /// Also execute the call with `system_dryRun` before broadcasting it.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+
+        self
+    }
+
+    pub fn get_tip(&self) -> u128 {
+        self.tip
+    }
+
+    pub fn get_era_period(&self) -> Option<u64> {
+        self.era_period
+    }
+
+    pub fn get_nonce(&self) -> Option<u64> {
+        self.nonce
+    }
+
+    pub fn get_target_block(&self) -> Option<crate::BlockHash> {
+        self.target_block
+    }
+
+    pub fn get_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub(crate) async fn build_params(
+        &self,
+        client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+        best_header: &crate::BlockHeader,
+    ) -> crate::Result<TxParams> {
+        let mut params_builder =
+            subxt::config::polkadot::PolkadotExtrinsicParamsBuilder::<subxt::PolkadotConfig>::new()
+                .tip(self.tip);
+
+        if let Some(era_period) = self.era_period {
+            let checkpoint_header = match self.target_block {
+                Some(block_hash) => client
+                    .rpc()
+                    .header(Some(block_hash))
+                    .await?
+                    .ok_or(crate::Error::BlockNotFound)?,
+                None => best_header.clone(),
+            };
+            params_builder = params_builder.mortal(&checkpoint_header, era_period);
+        }
+
+        Ok(params_builder.build())
+    }
+}
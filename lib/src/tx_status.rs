@@ -0,0 +1,192 @@
+/// Where a transaction submitted through the wallet is, see
+/// [`crate::WalletInstance::transaction_status`].
+#[derive(Clone, core::fmt::Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// In the pool of the node, not in a block yet.
+    Pending,
+    /// In a best block, which may still be retracted.
+    InBlock { block_hash: crate::BlockHash },
+    /// In a finalized block, `success` is false when the call failed to dispatch.
+    Finalized {
+        block_hash: crate::BlockHash,
+        success: bool,
+    },
+    /// Dropped, usurped or invalid, the transaction won't be included.
+    Failed { reason: String },
+}
+
+/// Statuses of the transactions submitted without waiting for them, each one followed by its own
+/// background task. Statuses of journaled transactions are written to the journal as well.
+///
+/// Only the last [`Self::MAX_SETTLED`] finalized or failed statuses are kept.
+pub(crate) struct TransactionTracker {
+    statuses: std::sync::RwLock<std::collections::HashMap<crate::ExtrinsicHash, TransactionStatus>>,
+    settled: std::sync::Mutex<std::collections::VecDeque<crate::ExtrinsicHash>>,
+    journal: std::sync::RwLock<Option<std::sync::Arc<crate::TransactionJournal>>>,
+    tasks: std::sync::Mutex<
+        std::collections::HashMap<crate::ExtrinsicHash, futures::future::AbortHandle>,
+    >,
+}
+
+impl TransactionTracker {
+    pub(crate) const MAX_SETTLED: usize = 10_000;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            statuses: std::sync::RwLock::new(std::collections::HashMap::new()),
+            settled: std::sync::Mutex::new(std::collections::VecDeque::new()),
+            journal: std::sync::RwLock::new(None),
+            tasks: std::sync::Mutex::new(std::collections::HashMap::new()),
+        }
+    }
+
+    pub(crate) fn status(&self, extrinsic_hash: crate::ExtrinsicHash) -> Option<TransactionStatus> {
+        self.statuses.read().unwrap().get(&extrinsic_hash).cloned()
+    }
+
+    pub(crate) fn set_journal(&self, journal: std::sync::Arc<crate::TransactionJournal>) {
+        *self.journal.write().unwrap() = Some(journal);
+    }
+
+    fn set_status(&self, extrinsic_hash: crate::ExtrinsicHash, status: TransactionStatus) {
+        if let Some(journal) = self.journal.read().unwrap().as_ref() {
+            if let Err(err) = journal.set_status(extrinsic_hash, status.clone().into()) {
+                nagara_logging::info!("Journal of {extrinsic_hash:?} wasn't updated: {err}");
+            }
+        }
+
+        self.statuses
+            .write()
+            .unwrap()
+            .insert(extrinsic_hash, status);
+    }
+
+    pub(crate) fn track(
+        self: &std::sync::Arc<Self>,
+        progress: crate::tx_options::TxProgress,
+    ) -> crate::ExtrinsicHash {
+        let extrinsic_hash = progress.extrinsic_hash();
+        self.set_status(extrinsic_hash, TransactionStatus::Pending);
+        let task = crate::task::spawn(follow_progress(self.clone(), progress));
+
+        // The same bytes broadcast again are followed by the new task only.
+        if let Some(previous_task) = self.tasks.lock().unwrap().insert(extrinsic_hash, task) {
+            previous_task.abort();
+        }
+
+        extrinsic_hash
+    }
+
+    /// Forget the task of a transaction that won't change anymore, and the oldest settled statuses
+    /// past [`Self::MAX_SETTLED`].
+    fn settle(&self, extrinsic_hash: crate::ExtrinsicHash) {
+        self.tasks.lock().unwrap().remove(&extrinsic_hash);
+        let mut settled = self.settled.lock().unwrap();
+        settled.push_back(extrinsic_hash);
+
+        while settled.len() > Self::MAX_SETTLED {
+            let Some(evicted_hash) = settled.pop_front() else {
+                break;
+            };
+            let mut statuses = self.statuses.write().unwrap();
+            // A transaction broadcast again is in the queue twice, or followed again.
+            let evictable = !settled.contains(&evicted_hash)
+                && matches!(
+                    statuses.get(&evicted_hash),
+                    Some(TransactionStatus::Finalized { .. } | TransactionStatus::Failed { .. })
+                );
+
+            if evictable {
+                statuses.remove(&evicted_hash);
+            }
+        }
+    }
+
+    pub(crate) fn abort(&self) {
+        for task in self.tasks.lock().unwrap().values() {
+            task.abort();
+        }
+    }
+}
+
+async fn follow_progress(
+    tracker: std::sync::Arc<TransactionTracker>,
+    mut progress: crate::tx_options::TxProgress,
+) {
+    let extrinsic_hash = progress.extrinsic_hash();
+
+    while let Some(status) = futures::StreamExt::next(&mut progress).await {
+        let status = match status {
+            Ok(subxt::tx::TxStatus::InBlock(in_block)) => TransactionStatus::InBlock {
+                block_hash: in_block.block_hash(),
+            },
+            Ok(subxt::tx::TxStatus::Retracted(_)) => TransactionStatus::Pending,
+            Ok(subxt::tx::TxStatus::Finalized(in_block)) => {
+                let success = match in_block.wait_for_success().await {
+                    Ok(_) => true,
+                    Err(subxt::Error::Runtime(_)) => false,
+                    Err(err) => {
+                        nagara_logging::info!("Outcome of {extrinsic_hash:?} is unknown: {err}");
+
+                        false
+                    }
+                };
+                tracker.set_status(
+                    extrinsic_hash,
+                    TransactionStatus::Finalized {
+                        block_hash: in_block.block_hash(),
+                        success,
+                    },
+                );
+
+                break;
+            }
+            Ok(subxt::tx::TxStatus::Usurped(_)) => TransactionStatus::Failed {
+                reason: "Usurped by another transaction with the same nonce".to_string(),
+            },
+            Ok(subxt::tx::TxStatus::Dropped) => TransactionStatus::Failed {
+                reason: "Dropped from the pool".to_string(),
+            },
+            Ok(subxt::tx::TxStatus::Invalid) => TransactionStatus::Failed {
+                reason: "Invalid".to_string(),
+            },
+            Ok(subxt::tx::TxStatus::FinalityTimeout(_)) => TransactionStatus::Failed {
+                reason: "Finality timeout".to_string(),
+            },
+            Ok(_) => continue,
+            Err(err) => TransactionStatus::Failed {
+                reason: err.to_string(),
+            },
+        };
+        tracker.set_status(extrinsic_hash, status);
+    }
+
+    tracker.settle(extrinsic_hash);
+}
+
+impl crate::WalletInstance {
+    /// Status of a transaction submitted by [`Self::submit_transfer`] or
+    /// [`Self::submit_payouts`], `None` for the others and for the ones settled long ago, which
+    /// the journal still has when one is set.
+    pub fn transaction_status(
+        &self,
+        extrinsic_hash: crate::ExtrinsicHash,
+    ) -> Option<TransactionStatus> {
+        self.transactions.status(extrinsic_hash)
+    }
+
+    pub(crate) async fn submit_tracked<Call: subxt::tx::TxPayload>(
+        &self,
+        sender_address: &str,
+        payouts: &[crate::Payout],
+        call: &Call,
+        options: &crate::TxOptions,
+    ) -> crate::Result<crate::ExtrinsicHash> {
+        let progress = self
+            .submit_with_options(sender_address, payouts, call, options)
+            .await?;
+
+        Ok(self.transactions.track(progress))
+    }
+}
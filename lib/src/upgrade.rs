@@ -0,0 +1,88 @@
+#[derive(Clone, core::fmt::Debug)]
+pub struct RuntimeUpgrade {
+    pub spec_version: u32,
+    pub transaction_version: u32,
+    /// `spec_version` recorded in `system.last_runtime_upgrade` after the upgrade.
+    pub last_upgrade_spec_version: Option<u32>,
+    pub runtime_mode: crate::RuntimeMode,
+}
+
+/// Follow the runtime version subscription, refresh the client metadata on every upgrade and
+/// revalidate the built-in metadata against it.
+pub(crate) async fn watch_runtime_upgrades(
+    client: subxt::OnlineClient<subxt::PolkadotConfig>,
+    network: crate::NetworkConfig,
+    runtime_mode: std::sync::Arc<std::sync::RwLock<crate::RuntimeMode>>,
+    listeners: std::sync::Arc<crate::task::Listeners<RuntimeUpgrade>>,
+) {
+    let updater = client.updater();
+    let mut updates = match updater.runtime_updates().await {
+        Ok(updates) => updates,
+        Err(err) => {
+            nagara_logging::info!("Runtime upgrades can't be followed: {err}");
+
+            return;
+        }
+    };
+
+    while let Some(update) = updates.next().await {
+        let update = match update {
+            Ok(update) => update,
+            Err(err) => {
+                nagara_logging::info!("Runtime upgrade notification failed: {err}");
+
+                continue;
+            }
+        };
+        let spec_version = update.runtime_version().spec_version;
+        let transaction_version = update.runtime_version().transaction_version;
+
+        if updater.apply_update(update).is_err() {
+            continue;
+        }
+
+        let new_runtime_mode = match crate::compat::verify_chain(&client, &network) {
+            Ok(new_runtime_mode) => new_runtime_mode,
+            Err(err) => {
+                nagara_logging::info!("Runtime upgrade made the wallet unusable: {err}");
+
+                crate::RuntimeMode::Incompatible
+            }
+        };
+        *runtime_mode.write().unwrap() = new_runtime_mode;
+        let last_upgrade_spec_version = fetch_last_upgrade_spec_version(&client).await;
+
+        nagara_logging::info!(
+            "Runtime upgraded to {spec_version}/{transaction_version} ({new_runtime_mode:?} mode)"
+        );
+        listeners.emit(RuntimeUpgrade {
+            spec_version,
+            transaction_version,
+            last_upgrade_spec_version,
+            runtime_mode: new_runtime_mode,
+        });
+    }
+}
+
+async fn fetch_last_upgrade_spec_version(
+    client: &subxt::OnlineClient<subxt::PolkadotConfig>,
+) -> Option<u32> {
+    let data_pointer = subxt::dynamic::storage(
+        "System",
+        "LastRuntimeUpgrade",
+        Vec::<subxt::dynamic::Value>::new(),
+    );
+    let last_upgrade = client
+        .storage()
+        .at_latest()
+        .await
+        .ok()?
+        .fetch(&data_pointer)
+        .await
+        .ok()??
+        .to_value()
+        .ok()?;
+    let spec_version = subxt::dynamic::At::at(&last_upgrade, "spec_version")?.as_u128()?;
+
+    u32::try_from(spec_version).ok()
+}
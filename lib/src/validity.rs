@@ -0,0 +1,133 @@
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidTransaction {
+    #[error("The call of the transaction is not expected")]
+    Call,
+    #[error("Inability to pay some fees (e.g. account balance too low)")]
+    Payment,
+    #[error("Transaction nonce is in the future")]
+    Future,
+    #[error("Transaction nonce was already used")]
+    Stale,
+    #[error("Transaction has a bad signature")]
+    BadProof,
+    #[error("Transaction birth block is ancient")]
+    AncientBirthBlock,
+    #[error("Transaction would exhaust the resources of the current block")]
+    ExhaustsResources,
+    #[error("Custom invalid transaction error {0}")]
+    Custom(u8),
+    #[error("Mandatory dispatch error")]
+    BadMandatory,
+    #[error("Transaction is mandatory and must not be validated")]
+    MandatoryValidation,
+    #[error("Transaction signer is not allowed")]
+    BadSigner,
+}
+
+#[derive(Clone, Copy, core::fmt::Debug, PartialEq, Eq, thiserror::Error)]
+pub enum UnknownTransaction {
+    #[error("Transaction signer could not be looked up")]
+    CannotLookup,
+    #[error("No validator found for the unsigned transaction")]
+    NoUnsignedValidator,
+    #[error("Custom unknown transaction error {0}")]
+    Custom(u8),
+}
+
+type RuntimeValidityError = crate::metadata::nagara::api::runtime_types::sp_runtime::transaction_validity::TransactionValidityError;
+type RuntimeInvalidTransaction = crate::metadata::nagara::api::runtime_types::sp_runtime::transaction_validity::InvalidTransaction;
+type RuntimeUnknownTransaction = crate::metadata::nagara::api::runtime_types::sp_runtime::transaction_validity::UnknownTransaction;
+
+impl From<RuntimeValidityError> for crate::Error {
+    fn from(value: RuntimeValidityError) -> Self {
+        match value {
+            RuntimeValidityError::Invalid(invalid) => Self::InvalidTransaction(match invalid {
+                RuntimeInvalidTransaction::Call => InvalidTransaction::Call,
+                RuntimeInvalidTransaction::Payment => InvalidTransaction::Payment,
+                RuntimeInvalidTransaction::Future => InvalidTransaction::Future,
+                RuntimeInvalidTransaction::Stale => InvalidTransaction::Stale,
+                RuntimeInvalidTransaction::BadProof => InvalidTransaction::BadProof,
+                RuntimeInvalidTransaction::AncientBirthBlock => {
+                    InvalidTransaction::AncientBirthBlock
+                }
+                RuntimeInvalidTransaction::ExhaustsResources => {
+                    InvalidTransaction::ExhaustsResources
+                }
+                RuntimeInvalidTransaction::Custom(code) => InvalidTransaction::Custom(code),
+                RuntimeInvalidTransaction::BadMandatory => InvalidTransaction::BadMandatory,
+                RuntimeInvalidTransaction::MandatoryValidation => {
+                    InvalidTransaction::MandatoryValidation
+                }
+                RuntimeInvalidTransaction::BadSigner => InvalidTransaction::BadSigner,
+            }),
+            RuntimeValidityError::Unknown(unknown) => Self::UnknownTransaction(match unknown {
+                RuntimeUnknownTransaction::CannotLookup => UnknownTransaction::CannotLookup,
+                RuntimeUnknownTransaction::NoUnsignedValidator => {
+                    UnknownTransaction::NoUnsignedValidator
+                }
+                RuntimeUnknownTransaction::Custom(code) => UnknownTransaction::Custom(code),
+            }),
+        }
+    }
+}
+
+impl crate::WalletInstance {
+    /// Check a signed extrinsic against the best block without broadcasting it.
+    ///
+    /// The transaction queue validation catches bad nonces, signatures and unpayable fees, the
+    /// optional dry run also executes the call to catch dispatch errors.
+    pub(crate) async fn validate_extrinsic(
+        &self,
+        extrinsic: &crate::tx_options::SignedExtrinsic,
+        dry_run: bool,
+    ) -> crate::Result<()> {
+        let best_block_hash = self
+            .client
+            .rpc()
+            .block_hash(None)
+            .await?
+            .ok_or(crate::Error::BlockNotFound)?;
+        let validate_call = crate::metadata::nagara::api::apis()
+            .tagged_transaction_queue()
+            .validate_transaction(
+                crate::metadata::nagara::api::runtime_types::sp_runtime::transaction_validity::TransactionSource::External,
+                extrinsic.encoded().to_vec().into(),
+                best_block_hash,
+            );
+        let validate_call = match self.compatible_runtime_mode()? {
+            crate::RuntimeMode::Static => validate_call,
+            _ => validate_call.unvalidated(),
+        };
+        self.client
+            .runtime_api()
+            .at(best_block_hash)
+            .call(validate_call)
+            .await??;
+
+        if !dry_run {
+            return Ok(());
+        }
+
+        let dry_run_result = self
+            .client
+            .rpc()
+            .dry_run(extrinsic.encoded(), Some(best_block_hash))
+            .await?;
+
+        if dry_run_result.0.first() == Some(&1) {
+            let validity_error = <RuntimeValidityError as parity_scale_codec::Decode>::decode(
+                &mut &dry_run_result.0[1..],
+            )
+            .map_err(|err| crate::Error::OtherError(err.to_string()))?;
+
+            return Err(validity_error.into());
+        }
+
+        match dry_run_result.into_dry_run_result(&self.client.metadata())? {
+            subxt::rpc::types::DryRunResult::DispatchError(dispatch_error) => {
+                Err(subxt::Error::Runtime(dispatch_error).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
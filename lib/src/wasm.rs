@@ -0,0 +1,227 @@
+//! JavaScript bindings of the wallet, every call talking to the node returns a `Promise`.
+//!
+//! Errors are JavaScript `Error`s carrying the code of [`crate::Error`] in their `code` property.
+//! Amounts and balances are decimal strings in the smallest unit of the native token.
+
+fn js_error(err: crate::Error) -> wasm_bindgen::JsValue {
+    let message = err.to_string();
+    let code = i32::from(err);
+    let js_error = js_sys::Error::new(&message);
+    let _ = js_sys::Reflect::set(&js_error, &"code".into(), &code.into());
+
+    js_error.into()
+}
+
+fn to_js<T: serde::Serialize>(value: &T) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue> {
+    let serializer =
+        serde_wasm_bindgen::Serializer::new().serialize_large_number_types_as_bigints(true);
+
+    serde::Serialize::serialize(value, &serializer)
+        .map_err(|err| js_error(crate::Error::OtherError(err.to_string())))
+}
+
+fn parse_amount(amount: &str) -> crate::Result<u128> {
+    amount
+        .parse()
+        .map_err(|err| crate::Error::InvalidArgument(format!("Invalid amount: {err}")))
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(js_name = Account)]
+pub struct JsAccount {
+    account: crate::OwnedAccount,
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(js_class = Account)]
+impl JsAccount {
+    /// Account of a private key hex or mnemonic, sr25519 unless `useSchnorrkel` is false.
+    #[wasm_bindgen::prelude::wasm_bindgen(constructor)]
+    pub fn new(secret: &str, use_schnorrkel: bool) -> Result<JsAccount, wasm_bindgen::JsValue> {
+        let account =
+            crate::OwnedAccount::new_from_str(secret, use_schnorrkel).map_err(js_error)?;
+
+        Ok(Self { account })
+    }
+
+    #[wasm_bindgen::prelude::wasm_bindgen(getter, js_name = mainAddress)]
+    pub fn main_address(&self) -> String {
+        self.account.get_main_address()
+    }
+
+    #[wasm_bindgen::prelude::wasm_bindgen(getter, js_name = storageAddress)]
+    pub fn storage_address(&self) -> String {
+        self.account.get_storage_address()
+    }
+
+    /// Raw sr25519 or ed25519 signature of the payload, the same one the wallet signs extrinsics
+    /// with.
+    pub fn sign(&self, payload: &[u8]) -> Vec<u8> {
+        match <crate::OwnedAccount as subxt::tx::Signer<subxt::PolkadotConfig>>::sign(
+            &self.account,
+            payload,
+        ) {
+            subxt::utils::MultiSignature::Sr25519(signature)
+            | subxt::utils::MultiSignature::Ed25519(signature) => signature.to_vec(),
+            subxt::utils::MultiSignature::Ecdsa(signature) => signature.to_vec(),
+        }
+    }
+}
+
+/// A wallet connected to a network, see [`JsWallet::connect`].
+///
+/// Accounts are added behind a write lock, calls already running finish before.
+#[wasm_bindgen::prelude::wasm_bindgen(js_name = Wallet)]
+pub struct JsWallet {
+    instance: std::rc::Rc<async_lock::RwLock<crate::WalletInstance>>,
+}
+
+#[wasm_bindgen::prelude::wasm_bindgen(js_class = Wallet)]
+impl JsWallet {
+    /// Connect to a network profile (`mainnet`, `testnet` or `local`), through `rpcUrls` instead of
+    /// its own endpoints when given. The node must have `genesisHash`, a `0x` prefixed hash, when
+    /// given.
+    pub async fn connect(
+        network: String,
+        rpc_urls: Option<Vec<String>>,
+        genesis_hash: Option<String>,
+    ) -> Result<JsWallet, wasm_bindgen::JsValue> {
+        let mut network = crate::NetworkConfig::from_name(&network).ok_or_else(|| {
+            js_error(crate::Error::InvalidArgument(format!(
+                "Unknown network {network}"
+            )))
+        })?;
+
+        if let Some(rpc_urls) = rpc_urls {
+            network = network.with_rpc_urls(&rpc_urls);
+        }
+
+        if let Some(genesis_hash) = genesis_hash {
+            let genesis_hash = <crate::BlockHash as core::str::FromStr>::from_str(&genesis_hash)
+                .map_err(|err| {
+                    js_error(crate::Error::InvalidArgument(format!(
+                        "Invalid genesis hash: {err}"
+                    )))
+                })?;
+            network = network.with_genesis_hash(genesis_hash);
+        }
+
+        let instance = crate::WalletInstance::create_with_network(network)
+            .await
+            .map_err(js_error)?;
+
+        Ok(Self {
+            instance: std::rc::Rc::new(async_lock::RwLock::new(instance)),
+        })
+    }
+
+    /// Add the account of a private key hex or mnemonic, resolves to its SS58 address.
+    #[wasm_bindgen::prelude::wasm_bindgen(js_name = addAccount)]
+    pub fn add_account(&self, secret: String, use_schnorrkel: bool) -> js_sys::Promise {
+        let instance = self.instance.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let account_address = instance
+                .write()
+                .await
+                .add_account(&secret, use_schnorrkel)
+                .map_err(js_error)?;
+
+            Ok(account_address.into())
+        })
+    }
+
+    /// Resolves to the total balance of the account at the latest block.
+    #[wasm_bindgen::prelude::wasm_bindgen(js_name = checkBalance)]
+    pub fn check_balance(&self, account_address: String) -> js_sys::Promise {
+        let instance = self.instance.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let balance = instance
+                .read()
+                .await
+                .check_balance(&account_address)
+                .await
+                .map_err(js_error)?;
+
+            Ok(balance.to_string().into())
+        })
+    }
+
+    /// Resolves to the free, reserved and frozen balances of the account at the latest block.
+    #[wasm_bindgen::prelude::wasm_bindgen(js_name = balanceInfo)]
+    pub fn balance_info(&self, account_address: String) -> js_sys::Promise {
+        let instance = self.instance.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let balance_info = instance
+                .read()
+                .await
+                .balance_info(&account_address)
+                .await
+                .map_err(js_error)?;
+
+            to_js(&balance_info)
+        })
+    }
+
+    /// Transfer from an account added to the wallet, resolves to the explorer URL of the including
+    /// block.
+    pub fn transfer(
+        &self,
+        sender_address: String,
+        recipient_address: String,
+        amount: String,
+    ) -> js_sys::Promise {
+        let instance = self.instance.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let amount = parse_amount(&amount).map_err(js_error)?;
+            let block_url = instance
+                .read()
+                .await
+                .transfer(&sender_address, &recipient_address, amount)
+                .await
+                .map_err(js_error)?;
+
+            Ok(block_url.into())
+        })
+    }
+
+    /// Resolves to the ledger entries of the account between both blocks, included.
+    #[wasm_bindgen::prelude::wasm_bindgen(js_name = accountHistory)]
+    pub fn account_history(
+        &self,
+        account_address: String,
+        from_block: u32,
+        to_block: u32,
+    ) -> js_sys::Promise {
+        let instance = self.instance.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let entries = instance
+                .read()
+                .await
+                .account_history(&account_address, from_block, to_block)
+                .await
+                .map_err(js_error)?;
+
+            to_js(&entries)
+        })
+    }
+
+    /// Resolves to the number of the latest finalized or best block.
+    #[wasm_bindgen::prelude::wasm_bindgen(js_name = latestBlock)]
+    pub fn latest_block(&self, finalized: bool) -> js_sys::Promise {
+        let instance = self.instance.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let block_number = instance
+                .read()
+                .await
+                .latest_block(finalized)
+                .await
+                .map_err(js_error)?;
+
+            Ok(block_number.into())
+        })
+    }
+}
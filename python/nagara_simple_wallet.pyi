@@ -0,0 +1,35 @@
+from typing import Any, Dict, List, Optional
+
+class WalletError(Exception): ...
+class RpcError(WalletError): ...
+class AccountNotFoundError(WalletError): ...
+class AccountFullError(WalletError): ...
+class BlockNotFoundError(WalletError): ...
+class ExtrinsicNotFoundError(WalletError): ...
+class TransactionNotPendingError(WalletError): ...
+class ReplacementError(WalletError): ...
+class InvalidTransactionError(WalletError): ...
+class NoHealthyEndpointError(WalletError): ...
+class IncompatibleChainError(WalletError): ...
+class InvalidArgumentError(WalletError): ...
+class PolicyViolationError(WalletError): ...
+
+class Wallet:
+    @staticmethod
+    async def connect(
+        network: str = "mainnet",
+        rpc_urls: Optional[List[str]] = None,
+        allow_dynamic: bool = False,
+        genesis_hash: Optional[str] = None,
+    ) -> "Wallet": ...
+    async def add_account(self, secret: str, use_schnorrkel: bool = True) -> str: ...
+    async def check_balance(self, account_address: str) -> int: ...
+    async def balance_info(self, account_address: str) -> Dict[str, Any]: ...
+    async def transfer(
+        self, sender_address: str, recipient_address: str, amount: int
+    ) -> str: ...
+    async def account_history(
+        self, account_address: str, from_block: int, to_block: int
+    ) -> List[Dict[str, Any]]: ...
+    async def latest_block(self, finalized: bool = True) -> int: ...
+    def block_url(self, block_hash: str) -> str: ...
@@ -0,0 +1,297 @@
+pyo3::create_exception!(
+    nagara_simple_wallet,
+    WalletError,
+    pyo3::exceptions::PyException
+);
+pyo3::create_exception!(nagara_simple_wallet, RpcError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, AccountNotFoundError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, AccountFullError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, BlockNotFoundError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, ExtrinsicNotFoundError, WalletError);
+pyo3::create_exception!(
+    nagara_simple_wallet,
+    TransactionNotPendingError,
+    WalletError
+);
+pyo3::create_exception!(nagara_simple_wallet, ReplacementError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, InvalidTransactionError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, NoHealthyEndpointError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, IncompatibleChainError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, InvalidArgumentError, WalletError);
+pyo3::create_exception!(nagara_simple_wallet, PolicyViolationError, WalletError);
+
+type ErrorArgs = (String, i32);
+
+/// Exception matching the error, raised with the message and the code of the error as arguments.
+fn py_error(err: nagara_simple_wallet::Error) -> pyo3::PyErr {
+    let new_err: fn(ErrorArgs) -> pyo3::PyErr = match &err {
+        nagara_simple_wallet::Error::SubxtError(_) => RpcError::new_err,
+        nagara_simple_wallet::Error::OtherError(_) => WalletError::new_err,
+        nagara_simple_wallet::Error::AccountNotFound => AccountNotFoundError::new_err,
+        nagara_simple_wallet::Error::AccountFull => AccountFullError::new_err,
+        nagara_simple_wallet::Error::BlockNotFound => BlockNotFoundError::new_err,
+        nagara_simple_wallet::Error::ExtrinsicNotFound => ExtrinsicNotFoundError::new_err,
+        nagara_simple_wallet::Error::TransactionNotPending => TransactionNotPendingError::new_err,
+        nagara_simple_wallet::Error::ReplacementUnderpriced
+        | nagara_simple_wallet::Error::ReplacementDropped => ReplacementError::new_err,
+        nagara_simple_wallet::Error::InvalidTransaction(_)
+        | nagara_simple_wallet::Error::UnknownTransaction(_) => InvalidTransactionError::new_err,
+        nagara_simple_wallet::Error::NoHealthyEndpoint => NoHealthyEndpointError::new_err,
+        nagara_simple_wallet::Error::GenesisMismatch { .. }
+        | nagara_simple_wallet::Error::IncompatibleRuntime { .. }
+        | nagara_simple_wallet::Error::MetadataNotFound(_) => IncompatibleChainError::new_err,
+        nagara_simple_wallet::Error::InvalidArgument(_) => InvalidArgumentError::new_err,
+        nagara_simple_wallet::Error::PolicyViolation(_) => PolicyViolationError::new_err,
+    };
+    let message = err.to_string();
+
+    new_err((message, i32::from(err)))
+}
+
+/// Python objects of a result, through its JSON so that 128 bit amounts stay exact `int`s.
+fn to_python<T: serde::Serialize>(value: &T) -> pyo3::PyResult<pyo3::PyObject> {
+    let json = serde_json::to_string(value)
+        .map_err(|err| py_error(nagara_simple_wallet::Error::OtherError(err.to_string())))?;
+
+    pyo3::Python::with_gil(|py| Ok(py.import("json")?.call_method1("loads", (json,))?.into()))
+}
+
+/// A wallet connected to a network, created with `await Wallet.connect(...)`.
+///
+/// Every method talking to the node returns an awaitable running on the tokio runtime of the
+/// module.
+#[pyo3::pyclass(name = "Wallet", module = "nagara_simple_wallet")]
+struct PyWallet {
+    network: nagara_simple_wallet::NetworkConfig,
+    instance: std::sync::Arc<tokio::sync::RwLock<nagara_simple_wallet::WalletInstance>>,
+}
+
+#[pyo3::pymethods]
+impl PyWallet {
+    /// Connect to a network profile (`mainnet`, `testnet` or `local`), through `rpc_urls` instead
+    /// of its own endpoints when given. The node must have `genesis_hash`, a `0x` prefixed hash,
+    /// when given.
+    #[staticmethod]
+    #[pyo3(signature = (network = "mainnet", rpc_urls = None, allow_dynamic = false, genesis_hash = None))]
+    fn connect<'py>(
+        py: pyo3::Python<'py>,
+        network: &str,
+        rpc_urls: Option<Vec<String>>,
+        allow_dynamic: bool,
+        genesis_hash: Option<&str>,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let mut network = nagara_simple_wallet::NetworkConfig::from_name(network)
+            .ok_or_else(|| {
+                py_error(nagara_simple_wallet::Error::InvalidArgument(format!(
+                    "Unknown network {network}"
+                )))
+            })?
+            .with_dynamic_fallback(allow_dynamic);
+
+        if let Some(rpc_urls) = rpc_urls {
+            network = network.with_rpc_urls(&rpc_urls);
+        }
+
+        if let Some(genesis_hash) = genesis_hash {
+            let genesis_hash =
+                <nagara_simple_wallet::BlockHash as core::str::FromStr>::from_str(genesis_hash)
+                    .map_err(|err| {
+                        py_error(nagara_simple_wallet::Error::InvalidArgument(format!(
+                            "Invalid genesis hash: {err}"
+                        )))
+                    })?;
+            network = network.with_genesis_hash(genesis_hash);
+        }
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let instance =
+                nagara_simple_wallet::WalletInstance::create_with_network(network.clone())
+                    .await
+                    .map_err(py_error)?;
+
+            Ok(Self {
+                network,
+                instance: std::sync::Arc::new(tokio::sync::RwLock::new(instance)),
+            })
+        })
+    }
+
+    /// Add the account of a private key hex or mnemonic, sr25519 unless `use_schnorrkel` is
+    /// false. Resolves to its SS58 address.
+    #[pyo3(signature = (secret, use_schnorrkel = true))]
+    fn add_account<'py>(
+        &self,
+        py: pyo3::Python<'py>,
+        secret: String,
+        use_schnorrkel: bool,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let instance = self.instance.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            instance
+                .write()
+                .await
+                .add_account(&secret, use_schnorrkel)
+                .map_err(py_error)
+        })
+    }
+
+    /// Resolves to the total balance of the account at the latest block, in the smallest unit.
+    fn check_balance<'py>(
+        &self,
+        py: pyo3::Python<'py>,
+        account_address: String,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let instance = self.instance.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            instance
+                .read()
+                .await
+                .check_balance(&account_address)
+                .await
+                .map_err(py_error)
+        })
+    }
+
+    /// Resolves to a dict of the free, reserved and frozen balances at the latest block.
+    fn balance_info<'py>(
+        &self,
+        py: pyo3::Python<'py>,
+        account_address: String,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let instance = self.instance.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let balance_info = instance
+                .read()
+                .await
+                .balance_info(&account_address)
+                .await
+                .map_err(py_error)?;
+
+            to_python(&balance_info)
+        })
+    }
+
+    /// Transfer `amount`, in the smallest unit, from an account added to the wallet. Resolves to
+    /// the explorer URL of the including block.
+    fn transfer<'py>(
+        &self,
+        py: pyo3::Python<'py>,
+        sender_address: String,
+        recipient_address: String,
+        amount: u128,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let instance = self.instance.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            instance
+                .read()
+                .await
+                .transfer(&sender_address, &recipient_address, amount)
+                .await
+                .map_err(py_error)
+        })
+    }
+
+    /// Resolves to the ledger entries, as dicts, of the account between both blocks included.
+    fn account_history<'py>(
+        &self,
+        py: pyo3::Python<'py>,
+        account_address: String,
+        from_block: u32,
+        to_block: u32,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let instance = self.instance.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let entries = instance
+                .read()
+                .await
+                .account_history(&account_address, from_block, to_block)
+                .await
+                .map_err(py_error)?;
+
+            to_python(&entries)
+        })
+    }
+
+    /// Resolves to the number of the latest finalized or best block.
+    #[pyo3(signature = (finalized = true))]
+    fn latest_block<'py>(
+        &self,
+        py: pyo3::Python<'py>,
+        finalized: bool,
+    ) -> pyo3::PyResult<&'py pyo3::PyAny> {
+        let instance = self.instance.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            instance
+                .read()
+                .await
+                .latest_block(finalized)
+                .await
+                .map_err(py_error)
+        })
+    }
+
+    /// Explorer URL of a `0x` prefixed block hash.
+    fn block_url(&self, block_hash: &str) -> pyo3::PyResult<String> {
+        let block_hash = <nagara_simple_wallet::BlockHash as core::str::FromStr>::from_str(
+            block_hash,
+        )
+        .map_err(|err| {
+            py_error(nagara_simple_wallet::Error::InvalidArgument(format!(
+                "Invalid block hash: {err}"
+            )))
+        })?;
+
+        Ok(self.network.block_url(block_hash))
+    }
+}
+
+#[pyo3::pymodule]
+#[pyo3(name = "nagara_simple_wallet")]
+fn python_module(py: pyo3::Python<'_>, module: &pyo3::types::PyModule) -> pyo3::PyResult<()> {
+    module.add_class::<PyWallet>()?;
+    module.add("WalletError", py.get_type::<WalletError>())?;
+    module.add("RpcError", py.get_type::<RpcError>())?;
+    module.add(
+        "AccountNotFoundError",
+        py.get_type::<AccountNotFoundError>(),
+    )?;
+    module.add("AccountFullError", py.get_type::<AccountFullError>())?;
+    module.add("BlockNotFoundError", py.get_type::<BlockNotFoundError>())?;
+    module.add(
+        "ExtrinsicNotFoundError",
+        py.get_type::<ExtrinsicNotFoundError>(),
+    )?;
+    module.add(
+        "TransactionNotPendingError",
+        py.get_type::<TransactionNotPendingError>(),
+    )?;
+    module.add("ReplacementError", py.get_type::<ReplacementError>())?;
+    module.add(
+        "InvalidTransactionError",
+        py.get_type::<InvalidTransactionError>(),
+    )?;
+    module.add(
+        "NoHealthyEndpointError",
+        py.get_type::<NoHealthyEndpointError>(),
+    )?;
+    module.add(
+        "IncompatibleChainError",
+        py.get_type::<IncompatibleChainError>(),
+    )?;
+    module.add(
+        "InvalidArgumentError",
+        py.get_type::<InvalidArgumentError>(),
+    )?;
+    module.add(
+        "PolicyViolationError",
+        py.get_type::<PolicyViolationError>(),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+"""Tests of the Python package against a running dev node.
+
+The node is `ws://127.0.0.1:9944` unless `NAGARA_WALLET_TEST_RPC_URL` says otherwise, the tests
+are skipped when it can't be reached. Transfers are only tested when `NAGARA_WALLET_TEST_SECRET`
+holds the private key of a funded account of the node.
+"""
+
+import os
+import socket
+import unittest
+import urllib.parse
+
+import nagara_simple_wallet
+
+RPC_URL = os.environ.get("NAGARA_WALLET_TEST_RPC_URL", "ws://127.0.0.1:9944")
+SECRET = os.environ.get("NAGARA_WALLET_TEST_SECRET")
+# Well-known development account `//Bob`.
+RECIPIENT = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+AMOUNT = 1_000_000_000
+
+
+def setUpModule():
+    url = urllib.parse.urlparse(RPC_URL)
+    port = url.port or (443 if url.scheme == "wss" else 80)
+
+    try:
+        socket.create_connection((url.hostname, port), timeout=2).close()
+    except OSError as err:
+        raise unittest.SkipTest(f"No dev node at {RPC_URL}: {err}")
+
+
+class WalletTest(unittest.IsolatedAsyncioTestCase):
+    async def asyncSetUp(self):
+        self.wallet = await nagara_simple_wallet.Wallet.connect(
+            "local", rpc_urls=[RPC_URL], allow_dynamic=True
+        )
+
+    async def test_unknown_network_is_invalid(self):
+        with self.assertRaises(nagara_simple_wallet.InvalidArgumentError):
+            await nagara_simple_wallet.Wallet.connect("nowhere")
+
+    async def test_malformed_genesis_hash_is_invalid(self):
+        with self.assertRaises(nagara_simple_wallet.InvalidArgumentError):
+            await nagara_simple_wallet.Wallet.connect(
+                "local", rpc_urls=[RPC_URL], genesis_hash="0x1234"
+            )
+
+    async def test_other_genesis_hash_is_refused(self):
+        with self.assertRaises(nagara_simple_wallet.IncompatibleChainError):
+            await nagara_simple_wallet.Wallet.connect(
+                "local",
+                rpc_urls=[RPC_URL],
+                allow_dynamic=True,
+                genesis_hash="0x" + "00" * 32,
+            )
+
+    async def test_malformed_address_is_invalid(self):
+        with self.assertRaises(nagara_simple_wallet.InvalidArgumentError) as raised:
+            await self.wallet.check_balance("not-an-address")
+
+        message, code = raised.exception.args
+        self.assertIn("not-an-address", message)
+        self.assertEqual(code, -15)
+
+    async def test_balance_info(self):
+        self.assertGreaterEqual(await self.wallet.check_balance(RECIPIENT), 0)
+
+        balance_info = await self.wallet.balance_info(RECIPIENT)
+        self.assertIn("free", balance_info)
+        self.assertIn("reserved", balance_info)
+
+    async def test_finalized_block_is_not_ahead_of_best(self):
+        finalized = await self.wallet.latest_block()
+        best = await self.wallet.latest_block(finalized=False)
+
+        self.assertLessEqual(finalized, best)
+
+    def test_block_url(self):
+        block_hash = "0x" + "ab" * 32
+
+        self.assertIn(block_hash, self.wallet.block_url(block_hash))
+
+        with self.assertRaises(nagara_simple_wallet.InvalidArgumentError):
+            self.wallet.block_url("0x1234")
+
+    async def test_transfer_without_account_is_refused(self):
+        with self.assertRaises(nagara_simple_wallet.AccountNotFoundError):
+            await self.wallet.transfer(RECIPIENT, RECIPIENT, AMOUNT)
+
+    @unittest.skipUnless(SECRET, "NAGARA_WALLET_TEST_SECRET is not set")
+    async def test_transfer(self):
+        sender = await self.wallet.add_account(SECRET)
+        from_block = await self.wallet.latest_block(finalized=False)
+        recipient_balance = await self.wallet.check_balance(RECIPIENT)
+
+        block_url = await self.wallet.transfer(sender, RECIPIENT, AMOUNT)
+
+        self.assertTrue(block_url.startswith("http"))
+        self.assertGreaterEqual(
+            await self.wallet.check_balance(RECIPIENT), recipient_balance + AMOUNT
+        )
+
+        to_block = await self.wallet.latest_block(finalized=False)
+        entries = await self.wallet.account_history(sender, from_block, to_block)
+        self.assertTrue(
+            any(entry["amount"] == AMOUNT and not entry["incoming"] for entry in entries)
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()